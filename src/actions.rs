@@ -1,58 +1,185 @@
+use crate::character::*;
 use crate::elements::*;
 use crate::player::*;
-use rand::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
-pub fn hint_random(hands: &mut Vec<Hand>) {
-    let mut rng = rand::rng();
-    let mut cable_ids: HashSet<u32> = HashSet::new();
-    for hand in hands.iter() {
-        let (_, &cable_id) = get_cables(hand).iter().enumerate().choose(&mut rng).unwrap();
-        cable_ids.insert(cable_id);
-    }
-    for id in cable_ids {
-        change_cable_status(id, hands, CableStatus::Clue);
-    }
-}
-
-pub fn duo_cut(player: u32, teammate: u32, position_self: u32, position_teammate: u32, hands: &mut Vec<Hand>, all_cables: &HashMap<u32, u32>) {
-    let hand_self = hands[player as usize].clone();
-    let hand_teammate = hands[teammate as usize].clone();
-    let cable_self_id = get_cables(&hand_self)[position_self as usize];
-    let cable_teammate_id = get_cables(&hand_teammate)[position_teammate as usize];
+/// Dual cut: the player points at a teammate's cable and claims it has the same value as one of their own cables.
+/// If the teammate's cable is red, it is cut and the bomb explodes.
+/// Returns whether the cut was successful.
+pub fn duo_cut(player: u32, teammate: u32, position_self: u32, position_teammate: u32, hands: &mut [Hand], all_cables: &HashMap<u32, u32>) -> bool {
+    let cable_self_id = get_cables(&hands[player as usize])[position_self as usize];
+    let cable_teammate_id = get_cables(&hands[teammate as usize])[position_teammate as usize];
     let value_self = get_value(cable_self_id, all_cables);
     let value_teammate = get_value(cable_teammate_id, all_cables);
-    println!("Value teammate: {}", value_teammate);
     if value_self == value_teammate {
         change_cable_status(cable_self_id, hands, CableStatus::Revealed);
         change_cable_status(cable_teammate_id, hands, CableStatus::Revealed);
-        println!("Duo cut successful! Both cables revealed.");
+        true
+    } else if get_color(cable_teammate_id, all_cables) == "red" {
+        change_cable_status(cable_teammate_id, hands, CableStatus::Revealed);
+        false
     } else {
         change_cable_status(cable_teammate_id, hands, CableStatus::Clue);
-        println!("Duo cut failed! Teammate's cable is now a clue.");
         add_announced_cable(&mut hands[player as usize], cable_self_id);
-        println!("Player announced cable value due to failed duo cut: {}", get_value(cable_self_id, &all_cables));
+        false
     }
-    
+}
+
+/// Solo cut: a player who holds all remaining uncut cables of a value cuts them at once.
+/// Red cables can never be cut.
+pub fn solo_cut(player: u32, value: u32, hands: &mut [Hand], all_cables: &HashMap<u32, u32>) -> Result<(), String> {
+    if value % 10 == 5 {
+        return Err("Red cables cannot be cut".to_string());
+    }
+    let mut own_cable_ids: Vec<u32> = vec![];
+    for (i, hand) in hands.iter().enumerate() {
+        for (id, status) in get_cables(hand).iter().zip(get_status(hand)) {
+            if *status == CableStatus::Revealed || get_value(*id, all_cables) != value {
+                continue;
+            }
+            if i as u32 != player {
+                return Err(format!("Not all remaining cables with value {} are in player {}'s hand", value as f32 / 10.0, player));
+            }
+            own_cable_ids.push(*id);
+        }
+    }
+    if own_cable_ids.is_empty() {
+        return Err(format!("Player {} has no uncut cable with value {}", player, value as f32 / 10.0));
+    }
+    for id in own_cable_ids {
+        change_cable_status(id, hands, CableStatus::Revealed);
+    }
+    Ok(())
+}
+
+/// Dual cut with the Double Detector ability: the player points at two cables of the same teammate.
+/// The cut succeeds if at least one of them has the value of the player's own cable.
+/// On a failed cut the info token goes on a cable which is not red. If both cables are red, one is cut and the bomb explodes.
+/// Returns whether the cut was successful.
+pub fn double_detector_cut(player: u32, teammate: u32, position_self: u32, positions_teammate: (u32, u32), hands: &mut [Hand], all_cables: &HashMap<u32, u32>, character: &mut Character) -> Result<bool, String> {
+    exhaust_ability(character, Ability::DoubleDetector)?;
+    let cable_self_id = get_cables(&hands[player as usize])[position_self as usize];
+    let cables_teammate = get_cables(&hands[teammate as usize]);
+    let first_id = cables_teammate[positions_teammate.0 as usize];
+    let second_id = cables_teammate[positions_teammate.1 as usize];
+    let value_self = get_value(cable_self_id, all_cables);
+
+    let matching_id = [first_id, second_id].into_iter().find(|id| get_value(*id, all_cables) == value_self);
+    match matching_id {
+        Some(id) => {
+            change_cable_status(cable_self_id, hands, CableStatus::Revealed);
+            change_cable_status(id, hands, CableStatus::Revealed);
+            Ok(true)
+        }
+        None => match [first_id, second_id].into_iter().find(|id| get_color(*id, all_cables) != "red") {
+            Some(id) => {
+                change_cable_status(id, hands, CableStatus::Clue);
+                add_announced_cable(&mut hands[player as usize], cable_self_id);
+                Ok(false)
+            }
+            None => {
+                change_cable_status(first_id, hands, CableStatus::Revealed);
+                Ok(false)
+            }
+        },
+    }
+}
+
+/// Rewinder ability: moves the detonator back by one step.
+pub fn use_rewinder(death_counter: &mut u32, character: &mut Character) -> Result<(), String> {
+    exhaust_ability(character, Ability::Rewinder)?;
+    *death_counter += 1;
+    Ok(())
+}
+
+/// Post-it ability: places an info token on one of the player's own hidden cables.
+pub fn use_post_it(player: u32, position: u32, hands: &mut [Hand], character: &mut Character) -> Result<(), String> {
+    if get_status(&hands[player as usize])[position as usize] != CableStatus::Hidden {
+        return Err(format!("The cable at position {} is not hidden", position));
+    }
+    exhaust_ability(character, Ability::PostIt)?;
+    let cable_id = get_cables(&hands[player as usize])[position as usize];
+    change_cable_status(cable_id, hands, CableStatus::Clue);
+    Ok(())
+}
+
+/// General Radar ability: for every player, tells whether they hold at least one not yet revealed cable with the given value.
+pub fn use_general_radar(value: u32, hands: &[Hand], all_cables: &HashMap<u32, u32>, character: &mut Character) -> Result<Vec<bool>, String> {
+    exhaust_ability(character, Ability::GeneralRadar)?;
+    Ok(radar_answer(value, hands, all_cables))
+}
+
+/// The answer of the General Radar: for every player, whether they hold a not yet revealed cable with the value.
+pub fn radar_answer(value: u32, hands: &[Hand], all_cables: &HashMap<u32, u32>) -> Vec<bool> {
+    hands.iter().map(|hand| {
+        get_cables(hand).iter().zip(get_status(hand))
+            .any(|(id, status)| *status != CableStatus::Revealed && get_value(*id, all_cables) == value)
+    }).collect()
+}
+
+/// Uses a label equipment to publicly mark the cables at `position` and `position + 1` in the player's own hand.
+/// The label has to tell the truth: `LabelKind::Equal` requires both cables to have the same value, `LabelKind::Different` requires different values.
+pub fn use_label(player: u32, position: usize, kind: LabelKind, hands: &mut [Hand], all_cables: &HashMap<u32, u32>) -> Result<(), String> {
+    let cables = get_cables(&hands[player as usize]);
+    if position + 1 >= cables.len() {
+        return Err(format!("Position {} has no right neighbour in player {}'s hand", position, player));
+    }
+    let status = get_status(&hands[player as usize]);
+    if status[position] == CableStatus::Revealed || status[position + 1] == CableStatus::Revealed {
+        return Err(format!("The cables at positions {} and {} have to be uncut", position, position + 1));
+    }
+    if get_label_at(&hands[player as usize], position).is_some() {
+        return Err(format!("There is already a label between positions {} and {}", position, position + 1));
+    }
+    let same_value = get_value(cables[position], all_cables) == get_value(cables[position + 1], all_cables);
+    match (kind, same_value) {
+        (LabelKind::Equal, false) => return Err("The cables have different values, the = label cannot be placed".to_string()),
+        (LabelKind::Different, true) => return Err("The cables have the same value, the ≠ label cannot be placed".to_string()),
+        _ => {}
+    }
+    add_label(&mut hands[player as usize], Label { position, kind });
+    Ok(())
+}
+
+/// Reveal red: a player whose remaining cables are all red turns them face up. They are not cut,
+/// the player just has nothing left to do and is skipped from now on.
+pub fn reveal_red(player: u32, hands: &mut [Hand], all_cables: &HashMap<u32, u32>) -> Result<(), String> {
+    let hand = &hands[player as usize];
+    let uncut: Vec<u32> = get_cables(hand).iter().zip(get_status(hand))
+        .filter(|(_, status)| **status != CableStatus::Revealed)
+        .map(|(id, _)| *id)
+        .collect();
+    if uncut.is_empty() {
+        return Err(format!("Player {} has no cables left", player));
+    }
+    if uncut.iter().any(|id| get_color(*id, all_cables) != "red") {
+        return Err(format!("Player {} still has cables which are not red", player));
+    }
+    for id in uncut {
+        change_cable_status(id, hands, CableStatus::Clue);
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::{GameOutcome, get_outcome};
 
     #[test]
-    fn test_hint_random() {
-        let mut hands = vec![
-            Hand::new(vec![1, 2, 3]),
-            Hand::new(vec![4, 5, 6, 7]),
-        ];
-        hint_random(&mut hands);
-
-        for i in 0..hands.len() {
-            let cnt_clue = get_status(&hands[i]).iter().filter(|&&status| status == CableStatus::Clue).count();
-            
-            assert!(cnt_clue == 1, "Expected exactly one cable to be changed in player {}'s hand, caught {}", i, cnt_clue);
-        }
+    fn test_solo_cut() {
+        let all_cables = HashMap::from([(1, 10), (2, 10), (3, 20), (4, 20), (5, 15)]);
+        let mut hands = init_hands(vec![
+            vec![1, 2, 3, 5],
+            vec![4],
+        ]);
+
+        assert!(solo_cut(0, 20, &mut hands, &all_cables).is_err(), "Expected solo cut to be rejected while a teammate holds the value");
+        assert!(solo_cut(0, 15, &mut hands, &all_cables).is_err(), "Expected solo cut of a red cable to be rejected");
+        assert!(solo_cut(1, 10, &mut hands, &all_cables).is_err(), "Expected solo cut without the value to be rejected");
+        assert!(solo_cut(0, 10, &mut hands, &all_cables).is_ok(), "Expected solo cut to succeed with all cables of the value in hand");
+        assert_eq!(get_status(&hands[0])[..2], [CableStatus::Revealed, CableStatus::Revealed], "Expected both cables with value 1 to be cut");
+        assert_eq!(get_status(&hands[0])[2], CableStatus::Hidden, "Expected other cables to stay hidden");
     }
 
     #[test]
@@ -62,14 +189,12 @@ mod tests {
             (8, 15), (9, 25), // red
             (11, 11), (12, 21), (13, 31), // yellow
         ]);
-        let mut hands = vec![
-            Hand::new(vec![1, 2, 3, 5, 6, 8, 11]),
-            Hand::new(vec![4, 7, 9, 10, 12, 13]),
-        ];
+        let mut hands = init_hands(vec![
+            vec![1, 2, 3, 5, 6, 8, 11],
+            vec![4, 7, 9, 10, 12, 13],
+        ]);
 
-        let hand0_cables = get_cables(&hands[0]);
         let hand0_status = get_status(&hands[0]);
-        let hand1_cables = get_cables(&hands[1]);
         let hand1_status = get_status(&hands[1]);
 
         assert!(matches!(hand0_status[1], CableStatus::Hidden | CableStatus::Clue), "Expected cable at position 2 in player 0's hand to be hidden or a clue before duo cut");
@@ -87,10 +212,10 @@ mod tests {
             (8, 15), (9, 25), // red
             (11, 11), (12, 21), (13, 31), // yellow
         ]);
-        let mut hands = vec![
-            Hand::new(vec![1, 2, 3, 5, 6, 8, 11]),
-            Hand::new(vec![4, 7, 9, 10, 12, 13]),
-        ];
+        let mut hands = init_hands(vec![
+            vec![1, 2, 3, 5, 6, 8, 11],
+            vec![4, 7, 9, 10, 12, 13],
+        ]);
 
         let hand0_status = get_status(&hands[0]);
         let hand1_status = get_status(&hands[1]);
@@ -104,4 +229,117 @@ mod tests {
         assert!(get_announced_cables(&hands[1]).is_empty(), "Expected no announced cables in player 1's hand after unsuccessful duo cut");
         
     }
+
+    #[test]
+    fn test_duo_cut_on_red_cable() {
+        let all_cables = HashMap::from([(1, 10), (2, 15), (3, 20)]);
+        let mut hands = init_hands(vec![
+            vec![1],
+            vec![2, 3],
+        ]);
+
+        assert!(!duo_cut(0, 1, 0, 0, &mut hands, &all_cables), "Expected duo cut on a red cable to fail");
+        assert_eq!(get_status(&hands[1])[0], CableStatus::Revealed, "Expected the red cable to be cut");
+    }
+
+    #[test]
+    fn test_reveal_red() {
+        let all_cables = HashMap::from([(1, 10), (2, 15), (3, 25)]);
+        let mut hands = init_hands(vec![vec![1, 2], vec![3]]);
+        assert!(reveal_red(0, &mut hands, &all_cables).is_err(), "Expected revealing to be rejected while a blue cable is left");
+        assert!(reveal_red(1, &mut hands, &all_cables).is_ok(), "Expected only red cables to be revealed");
+        assert_eq!(get_status(&hands[1]), [CableStatus::Clue], "Expected the red cable to be face up, not cut");
+        change_cable_status(1, &mut hands, CableStatus::Revealed);
+        assert!(reveal_red(0, &mut hands, &all_cables).is_ok(), "Expected revealing to be allowed once the blue cable is cut");
+    }
+
+    #[test]
+    fn test_use_label() {
+        let all_cables = HashMap::from([(1, 10), (2, 20), (3, 20), (4, 40)]);
+        let mut hands = init_hands(vec![vec![1, 2, 3, 4]]);
+
+        assert!(use_label(0, 0, LabelKind::Equal, &mut hands, &all_cables).is_err(), "Expected = label to be rejected for cables 1 and 2");
+        assert!(use_label(0, 1, LabelKind::Different, &mut hands, &all_cables).is_err(), "Expected ≠ label to be rejected for cables 2 and 2");
+        assert!(use_label(0, 3, LabelKind::Different, &mut hands, &all_cables).is_err(), "Expected label on the last cable to be rejected");
+
+        assert!(use_label(0, 1, LabelKind::Equal, &mut hands, &all_cables).is_ok(), "Expected = label to be placed for cables 2 and 2");
+        assert!(use_label(0, 2, LabelKind::Different, &mut hands, &all_cables).is_ok(), "Expected ≠ label to be placed for cables 2 and 4");
+        assert!(use_label(0, 1, LabelKind::Equal, &mut hands, &all_cables).is_err(), "Expected a second label at the same position to be rejected");
+        assert_eq!(get_labels(&hands[0]).len(), 2, "Expected two labels in player 0's hand");
+
+        change_cable_status(1, &mut hands, CableStatus::Revealed);
+        assert!(use_label(0, 0, LabelKind::Different, &mut hands, &all_cables).is_err(), "Expected a label next to a cut cable to be rejected");
+    }
+
+    #[test]
+    fn test_double_detector_cut() {
+        let all_cables = HashMap::from([(1, 10), (2, 20), (3, 10), (4, 20), (5, 30)]);
+        let mut hands = init_hands(vec![
+            vec![1, 2],
+            vec![3, 4, 5],
+        ]);
+        let mut character = Character::new("Captain", Ability::DoubleDetector);
+
+        let result = double_detector_cut(0, 1, 1, (0, 1), &mut hands, &all_cables, &mut character);
+        assert_eq!(result, Ok(true), "Expected double detector cut to succeed when the second cable matches");
+        assert_eq!(get_status(&hands[0])[1], CableStatus::Revealed, "Expected player 0's cable to be revealed");
+        assert_eq!(get_status(&hands[1])[1], CableStatus::Revealed, "Expected the matching teammate cable to be revealed");
+        assert_eq!(get_status(&hands[1])[0], CableStatus::Hidden, "Expected the other teammate cable to stay hidden");
+        assert!(double_detector_cut(0, 1, 0, (0, 2), &mut hands, &all_cables, &mut character).is_err(), "Expected the exhausted ability to be rejected");
+    }
+
+    #[test]
+    fn test_double_detector_cut_on_red_cables() {
+        let all_cables = HashMap::from([(1, 10), (2, 15), (3, 20), (4, 25)]);
+        let mut hands = init_hands(vec![
+            vec![1],
+            vec![2, 3, 4],
+        ]);
+        let mut character = Character::new("Captain", Ability::DoubleDetector);
+        assert_eq!(double_detector_cut(0, 1, 0, (0, 1), &mut hands, &all_cables, &mut character), Ok(false), "Expected the cut to fail");
+        assert_eq!(get_status(&hands[1])[..2], [CableStatus::Hidden, CableStatus::Clue], "Expected the info token on the cable which is not red");
+
+        let mut character = Character::new("Captain", Ability::DoubleDetector);
+        assert_eq!(double_detector_cut(0, 1, 0, (0, 2), &mut hands, &all_cables, &mut character), Ok(false), "Expected the cut to fail");
+        assert_eq!(get_status(&hands[1])[0], CableStatus::Revealed, "Expected a red cable to be cut when both are red");
+        assert_eq!(get_outcome(&hands, &all_cables, 3), Some(GameOutcome::RedCableCut), "Expected the bomb to explode");
+    }
+
+    #[test]
+    fn test_failed_double_detector_cut() {
+        let all_cables = HashMap::from([(1, 10), (2, 20), (3, 30), (4, 40)]);
+        let mut hands = init_hands(vec![
+            vec![1, 2],
+            vec![3, 4],
+        ]);
+        let mut character = Character::new("Captain", Ability::DoubleDetector);
+
+        let result = double_detector_cut(0, 1, 0, (0, 1), &mut hands, &all_cables, &mut character);
+        assert_eq!(result, Ok(false), "Expected double detector cut to fail when no cable matches");
+        assert_eq!(get_status(&hands[1])[0], CableStatus::Clue, "Expected the first teammate cable to be a clue");
+        assert!(get_announced_cables(&hands[0]).contains(&1), "Expected player 0 to announce the cable used for the cut");
+    }
+
+    #[test]
+    fn test_character_abilities() {
+        let all_cables = HashMap::from([(1, 10), (2, 20), (3, 10), (4, 40)]);
+        let mut hands = init_hands(vec![
+            vec![1, 2],
+            vec![3, 4],
+        ]);
+
+        let mut mechanic = Character::new("Mechanic", Ability::Rewinder);
+        let mut death_counter = 3;
+        assert!(use_rewinder(&mut death_counter, &mut mechanic).is_ok(), "Expected rewinder to be usable once");
+        assert_eq!(death_counter, 4, "Expected the detonator to move back by one");
+
+        let mut archivist = Character::new("Archivist", Ability::PostIt);
+        assert!(use_post_it(0, 1, &mut hands, &mut archivist).is_ok(), "Expected post-it to be placed on a hidden cable");
+        assert_eq!(get_status(&hands[0])[1], CableStatus::Clue, "Expected the cable to carry an info token");
+
+        let mut radio_operator = Character::new("Radio Operator", Ability::GeneralRadar);
+        let answers = use_general_radar(10, &hands, &all_cables, &mut radio_operator);
+        assert_eq!(answers, Ok(vec![true, true]), "Expected both players to hold a cable with value 1");
+        assert!(use_general_radar(40, &hands, &all_cables, &mut radio_operator).is_err(), "Expected general radar to be usable only once");
+    }
 }
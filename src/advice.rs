@@ -0,0 +1,225 @@
+//! This module estimates how likely a dual cut succeeds, from the view of one player.
+//! Complete deals which are consistent with the view are sampled on top of the deduced possible values,
+//! the probabilities are the shares of sampled deals in which the target cable has the value or is red.
+//! The sampling follows the real deal closely, but is not exactly uniform over all consistent deals.
+
+use rand::prelude::*;
+use std::collections::HashMap;
+
+use crate::deduction::{Possibilities, claimed_hidden, possible_values, remaining_copies};
+use crate::player::{CableStatus, LabelKind};
+use crate::view::PlayerView;
+
+/// Number of sampled deals behind the advice of the terminal.
+pub const ADVICE_SAMPLES: usize = 2000;
+
+/// A possible dual cut with its estimated chances.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CutAdvice {
+    pub teammate: u32,
+    /// Position of the targeted cable in the teammate's rack.
+    pub position: u32,
+    /// The announced value.
+    pub value: u32,
+    /// Position of an own uncut cable with the announced value.
+    pub position_self: u32,
+    /// Probability that the target has the announced value.
+    pub success: f64,
+    /// Probability that the target is a red cable.
+    pub red: f64,
+    /// Whether the deduction allows a red value for the target, even if no sampled deal drew one.
+    pub can_be_red: bool,
+}
+
+/// Samples a complete deal which is consistent with the view, values indexed by player and position.
+/// Returns `None` if the random choices ran into a contradiction, the caller simply tries again.
+pub fn sample_deal(view: &PlayerView, possible: &Possibilities, rng: &mut impl Rng) -> Option<Vec<Vec<u32>>> {
+    let remaining = remaining_copies(view);
+    let mut pool: HashMap<u32, u32> = remaining.iter()
+        .filter(|(value, _)| !view.red_values.contains(value) && !view.yellow_values.contains(value))
+        .map(|(value, count)| (*value, *count))
+        .collect();
+    // Choose which of the unseen red and yellow cables are in play.
+    for (values, keep) in [(&view.red_values, view.meta.red_keep), (&view.yellow_values, view.meta.yellow_keep)] {
+        let unseen: Vec<u32> = values.iter().copied().filter(|value| remaining[value] > 0).collect();
+        let seen = values.len() as u32 - unseen.len() as u32;
+        let left = keep.saturating_sub(seen) as usize;
+        for value in unseen.choose_multiple(rng, left) {
+            pool.insert(*value, 1);
+        }
+    }
+
+    // Runs of hidden cables between visible neighbours are drawn like a deal, restricted to the values which fit between the neighbours.
+    // The narrowest runs are drawn first, so that they are less likely to run out of values.
+    let mut deal: Vec<Vec<u32>> = view.racks.iter()
+        .map(|rack| rack.slots.iter().map(|slot| slot.value.unwrap_or(0)).collect())
+        .collect();
+    let mut runs: Vec<(usize, Vec<usize>, Vec<u32>)> = Vec::new();
+    for (r, rack) in view.racks.iter().enumerate() {
+        let mut run: Vec<usize> = Vec::new();
+        for i in 0..=rack.slots.len() {
+            if i < rack.slots.len() && rack.slots[i].value.is_none() {
+                run.push(i);
+            } else if !run.is_empty() {
+                let mut values: Vec<u32> = run.iter().flat_map(|i| possible[r][*i].iter().copied()).collect();
+                values.sort();
+                values.dedup();
+                runs.push((r, std::mem::take(&mut run), values));
+            }
+        }
+    }
+    runs.sort_by_key(|(_, run, values)| values.len() as i64 - run.len() as i64);
+
+    for (r, run, values) in runs {
+        let mut draw: Vec<u32> = values.iter()
+            .flat_map(|value| std::iter::repeat_n(*value, *pool.get(value).unwrap_or(&0) as usize))
+            .collect::<Vec<u32>>()
+            .choose_multiple(rng, run.len())
+            .copied()
+            .collect();
+        if draw.len() < run.len() {
+            return None;
+        }
+        draw.sort();
+        for (i, value) in run.iter().zip(draw) {
+            if !possible[r][*i].contains(&value) {
+                return None;
+            }
+            *pool.get_mut(&value).unwrap() -= 1;
+            deal[r][*i] = value;
+        }
+    }
+
+    let claimed = claimed_hidden(view);
+    for (r, rack) in view.racks.iter().enumerate() {
+        let values = &deal[r];
+        let sorted = values.windows(2).all(|w| w[0] <= w[1]);
+        let labels_hold = rack.labels.iter().all(|label| match label.kind {
+            LabelKind::Equal => values[label.position] == values[label.position + 1],
+            LabelKind::Different => values[label.position] != values[label.position + 1],
+        });
+        let claims_hold = claimed[r].iter().all(|(value, count)| {
+            let hidden = rack.slots.iter().zip(values).filter(|(slot, v)| slot.value.is_none() && *v == value).count();
+            hidden as u32 >= *count
+        });
+        if !sorted || !labels_hold || !claims_hold {
+            return None;
+        }
+    }
+    Some(deal)
+}
+
+/// Samples up to `samples` consistent deals. Gives up after a number of failed attempts, so fewer deals may be returned.
+pub fn sample_deals(view: &PlayerView, samples: usize, rng: &mut impl Rng) -> Vec<Vec<Vec<u32>>> {
+    let possible = possible_values(view);
+    let mut deals = Vec::with_capacity(samples);
+    let mut attempts = 0;
+    while deals.len() < samples && attempts < samples * 100 {
+        attempts += 1;
+        if let Some(deal) = sample_deal(view, &possible, rng) {
+            deals.push(deal);
+        }
+    }
+    deals
+}
+
+/// Estimates every dual cut the viewing player can make, the safest first.
+pub fn cut_advice(view: &PlayerView, samples: usize, rng: &mut impl Rng) -> Vec<CutAdvice> {
+    let deals = sample_deals(view, samples, rng);
+    if deals.is_empty() {
+        return Vec::new();
+    }
+    // Every value the player can announce, with the first own cable of that value.
+    let mut own_values: Vec<(u32, u32)> = Vec::new();
+    for (n, slot) in view.racks[view.player as usize].slots.iter().enumerate() {
+        let value = slot.value.expect("Own cables are visible");
+        if slot.status != CableStatus::Revealed && !view.red_values.contains(&value) && !own_values.iter().any(|(v, _)| *v == value) {
+            own_values.push((value, n as u32));
+        }
+    }
+
+    let possible = possible_values(view);
+    let share = |count: usize| count as f64 / deals.len() as f64;
+    let mut advice = Vec::new();
+    for (r, rack) in view.racks.iter().enumerate() {
+        if r as u32 == view.player {
+            continue;
+        }
+        for (i, slot) in rack.slots.iter().enumerate() {
+            if slot.status == CableStatus::Revealed {
+                continue;
+            }
+            let red = share(deals.iter().filter(|deal| view.red_values.contains(&deal[r][i])).count());
+            let can_be_red = possible[r][i].iter().any(|value| view.red_values.contains(value));
+            for (value, position_self) in &own_values {
+                advice.push(CutAdvice {
+                    teammate: r as u32,
+                    position: i as u32,
+                    value: *value,
+                    position_self: *position_self,
+                    success: share(deals.iter().filter(|deal| deal[r][i] == *value).count()),
+                    red,
+                    can_be_red,
+                });
+            }
+        }
+    }
+    advice.sort_by(|a, b| b.success.total_cmp(&a.success).then(a.red.total_cmp(&b.red)));
+    advice
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{new_game, state_with_hands};
+    use rand_chacha::ChaCha8Rng;
+    use crate::game::GameMeta;
+    use crate::player::change_cable_status;
+    use crate::view::player_view;
+
+    #[test]
+    fn test_certain_cuts() {
+        // Values 1 and 2 with four copies each and one red cable 2.5, which is the highest cable of player 1.
+        let mut all_cables: HashMap<u32, u32> = (0..8).map(|i| (i + 1, (i / 4 + 1) * 10)).collect();
+        all_cables.insert(9, 25);
+        let state = state_with_hands(GameMeta::new(2, 1, 1, 0, 0, 2), all_cables, vec![
+            vec![1, 2, 5, 6],
+            vec![3, 4, 7, 8, 9],
+        ]);
+        let advice = cut_advice(&player_view(&state, 0), 200, &mut ChaCha8Rng::seed_from_u64(1));
+        assert_eq!(advice.len(), 10, "Expected two values for each of the five cables of player 1");
+        assert_eq!(advice[0].success, 1.0, "Expected a certain cut to come first");
+
+        let find = |position: u32, value: u32| advice.iter().find(|a| a.position == position && a.value == value).unwrap();
+        assert_eq!(find(0, 10).success, 1.0, "Expected the lowest cable to be a 1");
+        assert_eq!(find(0, 20).success, 0.0, "Expected the lowest cable not to be a 2");
+        assert_eq!(find(0, 10).position_self, 0, "Expected the first own 1 to be used");
+        assert_eq!(find(4, 20).red, 1.0, "Expected the highest cable to be the red one");
+        assert!(find(4, 20).can_be_red, "Expected the highest cable to possibly be red");
+
+        let mut state = state;
+        change_cable_status(3, &mut state.hands, CableStatus::Clue);
+        let advice = cut_advice(&player_view(&state, 0), 20, &mut ChaCha8Rng::seed_from_u64(1));
+        assert!(advice.iter().filter(|a| a.position == 0).all(|a| !a.can_be_red), "Expected a cable with an info token not to be red");
+    }
+
+    #[test]
+    fn test_probabilities_of_random_game() {
+        let state = new_game(GameMeta::new(8, 2, 1, 2, 1, 3), None, 5);
+        let view = player_view(&state, 1);
+        let advice = cut_advice(&view, 100, &mut ChaCha8Rng::seed_from_u64(2));
+        assert!(!advice.is_empty(), "Expected consistent deals to be found");
+        for a in &advice {
+            assert!((0.0..=1.0).contains(&a.success) && (0.0..=1.0).contains(&a.red), "Expected probabilities between 0 and 1");
+            assert_ne!(a.teammate, 1, "Expected only teammates as targets");
+        }
+        for deal in sample_deals(&view, 20, &mut ChaCha8Rng::seed_from_u64(3)) {
+            for (r, rack) in view.racks.iter().enumerate() {
+                assert!(deal[r].windows(2).all(|w| w[0] <= w[1]), "Expected sampled racks to be sorted");
+                for (slot, value) in rack.slots.iter().zip(&deal[r]) {
+                    assert!(slot.value.is_none_or(|v| v == *value), "Expected visible cables to keep their value");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! This module contains the bots. They only see their `PlayerView` and decide with the deduction engine:
+//! a solo cut whenever possible, otherwise the dual cut with the highest chance on a cable which cannot be red.
+//! If every cable can be red, a cable which was red in no sampled deal is preferred.
+
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use crate::advice::cut_advice;
+use crate::deduction::value_copies;
+use crate::player::CableStatus;
+use crate::seat::{Move, Player, Turn};
+use crate::view::PlayerView;
+
+/// Number of sampled deals the heuristic bot bases a dual cut on.
+pub const BOT_SAMPLES: usize = 200;
+
+/// A rule-based bot.
+pub struct HeuristicBot {
+    rng: ChaCha8Rng,
+}
+
+impl HeuristicBot {
+    pub fn new(seed: u64) -> Self {
+        HeuristicBot { rng: ChaCha8Rng::seed_from_u64(seed) }
+    }
+}
+
+impl Player for HeuristicBot {
+    fn choose_token(&mut self, view: &PlayerView) -> u32 {
+        token_position(view)
+    }
+
+    fn choose_turn(&mut self, view: &PlayerView) -> Turn {
+        if only_red_left(view) {
+            return Turn::Play(Move::RevealRed);
+        }
+        if let Some(value) = solo_cut_value(view) {
+            return Turn::Play(Move::SoloCut { value });
+        }
+        let advice = cut_advice(view, BOT_SAMPLES, &mut self.rng);
+        match advice.iter().find(|a| !a.can_be_red).or(advice.iter().find(|a| a.red == 0.0)).or(advice.first()) {
+            Some(a) => Turn::Play(Move::DualCut { teammate: a.teammate, position_self: a.position_self, position_teammate: a.position }),
+            None => Turn::Play(fallback_move(view)),
+        }
+    }
+}
+
+/// Places the info token on a blue cable whose value the player holds only once, as close to the middle of the rack as possible.
+/// These tokens tell the teammates the most. Without such a cable, the middle blue cable is taken.
+pub fn token_position(view: &PlayerView) -> u32 {
+    let values: Vec<u32> = view.racks[view.player as usize].slots.iter().map(|slot| slot.value.expect("Own cables are visible")).collect();
+    let middle = values.len() as i64 / 2;
+    let blue: Vec<usize> = (0..values.len()).filter(|i| values[*i].is_multiple_of(10)).collect();
+    let single: Vec<usize> = blue.iter().copied().filter(|i| values.iter().filter(|v| **v == values[*i]).count() == 1).collect();
+    let candidates = if single.is_empty() { blue } else { single };
+    candidates.into_iter().min_by_key(|i| (*i as i64 - middle).abs()).unwrap_or(0) as u32
+}
+
+/// Returns whether the player has uncut cables left and all of them are red, so that they can only be revealed.
+pub fn only_red_left(view: &PlayerView) -> bool {
+    let uncut: Vec<_> = view.racks[view.player as usize].slots.iter()
+        .filter(|slot| slot.status != CableStatus::Revealed)
+        .collect();
+    !uncut.is_empty() && uncut.iter().all(|slot| slot.value.is_some_and(|value| view.red_values.contains(&value)))
+}
+
+/// Returns a value the player can solo cut: all cables of the value which are not cut yet are in the own rack.
+pub fn solo_cut_value(view: &PlayerView) -> Option<u32> {
+    let copies = value_copies(view);
+    let own = &view.racks[view.player as usize];
+    let mut values: Vec<u32> = own.slots.iter()
+        .filter(|slot| slot.status != CableStatus::Revealed)
+        .filter_map(|slot| slot.value)
+        .filter(|value| !view.red_values.contains(value))
+        .collect();
+    values.dedup();
+    values.into_iter().find(|value| {
+        let uncut_own = own.slots.iter().filter(|slot| slot.status != CableStatus::Revealed && slot.value == Some(*value)).count();
+        let cut = view.racks.iter()
+            .flat_map(|rack| rack.slots.iter())
+            .filter(|slot| slot.status == CableStatus::Revealed && slot.value == Some(*value))
+            .count();
+        (uncut_own + cut) as u32 == copies[value]
+    })
+}
+
+/// A dual cut of the first uncut own cable on the first uncut cable of a teammate, if nothing better is known.
+pub fn fallback_move(view: &PlayerView) -> Move {
+    let uncut = |player: usize| view.racks[player].slots.iter().position(|slot| slot.status != CableStatus::Revealed);
+    let position_self = uncut(view.player as usize).unwrap_or(0) as u32;
+    let (teammate, position_teammate) = (0..view.racks.len())
+        .filter(|i| *i as u32 != view.player)
+        .find_map(|i| uncut(i).map(|position| (i as u32, position as u32)))
+        .unwrap_or(((view.player + 1) % view.racks.len() as u32, 0));
+    Move::DualCut { teammate, position_self, position_teammate }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::state_with_hands;
+    use std::collections::HashMap;
+    use crate::game::{GameMeta, GameState, get_outcome};
+    use crate::player::change_cable_status;
+    use crate::montecarlo::Budget;
+    use crate::seat::{place_tokens, play_move, Seat, init_players};
+    use crate::view::player_view;
+
+    #[test]
+    fn test_solo_cut_value() {
+        let all_cables: HashMap<u32, u32> = (0..8).map(|i| (i + 1, (i / 4 + 1) * 10)).collect();
+        let state = state_with_hands(GameMeta::new(2, 0, 0, 0, 0, 2), all_cables, vec![vec![1, 2, 5, 6, 7, 8], vec![3, 4]]);
+        let view = player_view(&state, 0);
+        assert_eq!(solo_cut_value(&view), Some(20), "Expected a solo cut of the 2s held by the player");
+        assert_eq!(solo_cut_value(&player_view(&state, 1)), None, "Expected no solo cut with only half of the 1s");
+        assert_eq!(token_position(&view), 3, "Expected the token on the middle cable, no value is held once");
+    }
+
+    #[test]
+    fn test_only_red_left() {
+        let all_cables = HashMap::from([(1, 10), (2, 15), (3, 20), (4, 10)]);
+        let mut state = state_with_hands(GameMeta::new(2, 1, 1, 0, 0, 2), all_cables, vec![vec![1, 2], vec![3, 4]]);
+        assert!(!only_red_left(&player_view(&state, 0)), "Expected a blue cable to be left");
+        change_cable_status(1, &mut state.hands, CableStatus::Revealed);
+        assert!(only_red_left(&player_view(&state, 0)), "Expected only the red cable to be left");
+        change_cable_status(2, &mut state.hands, CableStatus::Revealed);
+        assert!(!only_red_left(&player_view(&state, 0)), "Expected nothing to reveal without uncut cables");
+    }
+
+    #[test]
+    fn test_bots_finish_game() {
+        let mut state = GameState::deal(GameMeta::new(6, 1, 1, 0, 0, 3), None, 8);
+        let mut players = init_players(&[Seat::Bot; 3], 8, Budget::default(), false);
+        place_tokens(&mut state, &mut players).unwrap();
+        for _ in 0..200 {
+            if get_outcome(&state.hands, &state.all_cables, state.death_counter).is_some() {
+                return;
+            }
+            let player = state.current_player;
+            let Turn::Play(mv) = players[player as usize].choose_turn(&player_view(&state, player)) else {
+                panic!("Expected bots to only play moves");
+            };
+            play_move(&mut state, mv).expect("Expected bots to only make valid moves");
+        }
+        panic!("Expected the game to end");
+    }
+}
@@ -0,0 +1,166 @@
+//! This module keeps track of the campaign progress of a local group: which missions were completed,
+//! how many attempts they took and the best remaining detonator value.
+//! The progress is stored as JSON in the user's data directory.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::GameOutcome;
+use crate::mission::mission_catalogue;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Campaign {
+    pub missions: Vec<MissionRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MissionRecord {
+    pub number: u32,
+    pub attempts: u32,
+    pub completed: bool,
+    /// Highest remaining detonator value of all successful attempts.
+    pub best_detonator: Option<u32>,
+    /// Date of the first successful attempt, formatted as YYYY-MM-DD.
+    pub completed_on: Option<String>,
+}
+
+/// Returns the path of the campaign file, or `None` if no data directory can be found.
+/// Uses `$XDG_DATA_HOME`, `$HOME/.local/share` or `%APPDATA%`, in this order.
+pub fn campaign_path() -> Option<PathBuf> {
+    let data_dir = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(data_dir.join("bomb-busters").join("campaign.json"))
+}
+
+/// Loads the campaign from a file. A missing file is an empty campaign.
+pub fn load_campaign(path: &Path) -> Result<Campaign, String> {
+    if !path.exists() {
+        return Ok(Campaign::default());
+    }
+    let data = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read campaign file {}: {}", path.display(), e))?;
+    serde_json::from_str(&data).map_err(|e| format!("Invalid campaign file {}: {}", path.display(), e))
+}
+
+pub fn save_campaign(campaign: &Campaign, path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    }
+    let data = serde_json::to_string_pretty(campaign).map_err(|e| e.to_string())?;
+    fs::write(path, data).map_err(|e| format!("Could not write campaign file {}: {}", path.display(), e))
+}
+
+pub fn get_record(campaign: &Campaign, number: u32) -> Option<&MissionRecord> {
+    campaign.missions.iter().find(|record| record.number == number)
+}
+
+/// Records an attempt of a mission. `remaining_detonator` is the detonator value at the end of the game.
+pub fn record_attempt(campaign: &mut Campaign, number: u32, outcome: GameOutcome, remaining_detonator: u32, date: &str) {
+    let index = match campaign.missions.iter().position(|record| record.number == number) {
+        Some(index) => index,
+        None => {
+            campaign.missions.push(MissionRecord {
+                number,
+                attempts: 0,
+                completed: false,
+                best_detonator: None,
+                completed_on: None,
+            });
+            campaign.missions.sort_by_key(|record| record.number);
+            campaign.missions.iter().position(|record| record.number == number).unwrap()
+        }
+    };
+    let record = &mut campaign.missions[index];
+    record.attempts += 1;
+    if outcome == GameOutcome::Defused {
+        if !record.completed {
+            record.completed_on = Some(date.to_string());
+        }
+        record.completed = true;
+        record.best_detonator = Some(record.best_detonator.map_or(remaining_detonator, |best| best.max(remaining_detonator)));
+    }
+}
+
+/// Returns the number of the first built-in mission which has not been completed yet.
+pub fn next_mission(campaign: &Campaign) -> Option<u32> {
+    mission_catalogue()
+        .iter()
+        .map(|mission| mission.number)
+        .find(|number| !get_record(campaign, *number).is_some_and(|record| record.completed))
+}
+
+/// Returns today's date (UTC) formatted as YYYY-MM-DD.
+pub fn today() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    format_date(seconds / 86400)
+}
+
+/// Formats a number of days since 1970-01-01 as YYYY-MM-DD.
+fn format_date(days: u64) -> String {
+    // Civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_attempt() {
+        let mut campaign = Campaign::default();
+        record_attempt(&mut campaign, 2, GameOutcome::RedCableCut, 3, "2025-01-01");
+        record_attempt(&mut campaign, 2, GameOutcome::Defused, 1, "2025-01-02");
+        record_attempt(&mut campaign, 2, GameOutcome::Defused, 3, "2025-01-03");
+        record_attempt(&mut campaign, 1, GameOutcome::Defused, 2, "2025-01-04");
+
+        let record = get_record(&campaign, 2).expect("Mission 2 should be recorded");
+        assert_eq!(record.attempts, 3, "Expected 3 attempts, found {}", record.attempts);
+        assert!(record.completed, "Expected mission 2 to be completed");
+        assert_eq!(record.best_detonator, Some(3), "Expected the best remaining detonator to be kept");
+        assert_eq!(record.completed_on.as_deref(), Some("2025-01-02"), "Expected the date of the first success");
+        assert_eq!(campaign.missions[0].number, 1, "Expected records to be sorted by mission number");
+    }
+
+    #[test]
+    fn test_next_mission() {
+        let mut campaign = Campaign::default();
+        assert_eq!(next_mission(&campaign), Some(1), "Expected mission 1 for a new campaign");
+        record_attempt(&mut campaign, 1, GameOutcome::Defused, 2, "2025-01-01");
+        record_attempt(&mut campaign, 2, GameOutcome::DetonatorExpired, 0, "2025-01-01");
+        assert_eq!(next_mission(&campaign), Some(2), "Expected the failed mission 2 to be next");
+    }
+
+    #[test]
+    fn test_save_and_load_campaign() {
+        let path = env::temp_dir().join(format!("bomb-busters-test-{}", std::process::id())).join("campaign.json");
+        assert_eq!(load_campaign(&path), Ok(Campaign::default()), "Expected a missing file to be an empty campaign");
+
+        let mut campaign = Campaign::default();
+        record_attempt(&mut campaign, 1, GameOutcome::Defused, 2, "2025-01-01");
+        save_campaign(&campaign, &path).expect("Campaign should be saved");
+        assert_eq!(load_campaign(&path), Ok(campaign), "Expected the loaded campaign to match the saved one");
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(19723), "2024-01-01");
+        assert_eq!(format_date(19782), "2024-02-29");
+    }
+}
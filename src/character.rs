@@ -0,0 +1,109 @@
+//! This module contains the character cards. Every player is dealt one character at the start of the game.
+//! Each character has a personal ability which can be used once per game and is exhausted afterwards.
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The personal abilities of the characters.
+#[derive(Clone, Debug, PartialEq, Copy, Serialize, Deserialize)]
+pub enum Ability {
+    /// In a dual cut, point at two cables of the same teammate. The cut succeeds if either of them matches.
+    DoubleDetector,
+    /// Moves the detonator back by one step.
+    Rewinder,
+    /// Places an info token on one of the player's own cables.
+    PostIt,
+    /// Announces a value, every player tells whether they hold at least one hidden cable with that value.
+    GeneralRadar,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Character {
+    name: String,
+    ability: Ability,
+    ability_used: bool,
+}
+
+impl Character {
+    pub fn new(name: &str, ability: Ability) -> Self {
+        Character { name: name.to_string(), ability, ability_used: false }
+    }
+}
+
+/// All characters of the game with their abilities.
+const CHARACTERS: [(&str, Ability); 5] = [
+    ("Captain", Ability::DoubleDetector),
+    ("Technician", Ability::DoubleDetector),
+    ("Mechanic", Ability::Rewinder),
+    ("Archivist", Ability::PostIt),
+    ("Radio Operator", Ability::GeneralRadar),
+];
+
+/// Deals one random character to every player. No character is dealt twice.
+pub fn init_characters(number_players: u32, rng: &mut impl Rng) -> Vec<Character> {
+    CHARACTERS
+        .iter()
+        .choose_multiple(rng, number_players as usize)
+        .into_iter()
+        .map(|(name, ability)| Character::new(name, *ability))
+        .collect()
+}
+
+pub fn get_name(character: &Character) -> String {
+    character.name.clone()
+}
+
+pub fn get_ability(character: &Character) -> Ability {
+    character.ability
+}
+
+pub fn is_ability_used(character: &Character) -> bool {
+    character.ability_used
+}
+
+pub fn ability_name(ability: Ability) -> String {
+    match ability {
+        Ability::DoubleDetector => "Double Detector".to_string(),
+        Ability::Rewinder => "Rewinder".to_string(),
+        Ability::PostIt => "Post-it".to_string(),
+        Ability::GeneralRadar => "General Radar".to_string(),
+    }
+}
+
+/// Marks the ability of the character as used.
+/// Fails if the character does not have this ability or has already used it.
+pub fn exhaust_ability(character: &mut Character, ability: Ability) -> Result<(), String> {
+    if character.ability != ability {
+        return Err(format!("{} does not have the {} ability", character.name, ability_name(ability)));
+    }
+    if character.ability_used {
+        return Err(format!("{} has already used the {} ability", character.name, ability_name(ability)));
+    }
+    character.ability_used = true;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_init_characters() {
+        let characters = init_characters(4, &mut rand::rng());
+        assert_eq!(characters.len(), 4, "Expected 4 characters, found {}", characters.len());
+        let names: HashSet<String> = characters.iter().map(get_name).collect();
+        assert_eq!(names.len(), 4, "Expected every player to get a different character");
+        assert!(characters.iter().all(|c| !is_ability_used(c)), "Abilities should be available at the start of the game");
+    }
+
+    #[test]
+    fn test_exhaust_ability() {
+        let mut character = Character::new("Mechanic", Ability::Rewinder);
+        assert!(exhaust_ability(&mut character, Ability::PostIt).is_err(), "Expected ability of another character to be rejected");
+        assert!(!is_ability_used(&character), "Ability should still be available after a rejected use");
+        assert!(exhaust_ability(&mut character, Ability::Rewinder).is_ok(), "Expected first use of the ability to succeed");
+        assert!(is_ability_used(&character), "Ability should be used after the first use");
+        assert!(exhaust_ability(&mut character, Ability::Rewinder).is_err(), "Expected second use of the ability to be rejected");
+    }
+}
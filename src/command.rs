@@ -0,0 +1,139 @@
+//! This module contains the command language of the terminal. Every turn, a player types one command per line,
+//! e.g. `dual 2 5 3` to cut player 2's cable #5 with the own cable #3. Parsing errors tell what is wrong with the command.
+
+use crate::elements::Equipment;
+use crate::notation::parse_value;
+
+/// Explanation of all commands, shown by `help`.
+pub const COMMAND_HELP: &str = "\
+Commands:
+  dual <teammate> <their pos> <my pos>    cut a teammate's cable with one of your own cables
+  double <teammate> <pos> <pos> <my pos>  Double Detector: dual cut on two of a teammate's cables at once
+  solo <value>                            cut all remaining cables of a value, e.g. 'solo 4' or 'solo 1.1'
+  reveal-red                              turn your cables face up once only red cables are left
+  use <equipment> <pos>                   use 'label-equal' or 'label-different' on your cables <pos> and <pos + 1>
+  rewind                                  Rewinder: move the detonator back by one step
+  post-it <pos>                           Post-it: place an info token on your own cable
+  radar <value>                           General Radar: ask every player whether they hold a value
+  view                                    show the cables and characters again
+  advise                                  list the safest dual cuts
+  assist                                  switch the value ranges in the teammates' racks on or off
+  save <file> / load <file>               save or load the game
+  undo / redo                             undo or redo the last action in a casual game
+  help                                    show this list";
+
+/// A command of a player at the terminal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Dual { teammate: u32, position_teammate: u32, position_self: u32 },
+    Double { teammate: u32, positions_teammate: (u32, u32), position_self: u32 },
+    Solo { value: u32 },
+    RevealRed,
+    Use { equipment: Equipment, position: u32 },
+    Rewind,
+    PostIt { position: u32 },
+    Radar { value: u32 },
+    View,
+    Help,
+    Advise,
+    Assist,
+    Save(String),
+    Load(String),
+    Undo,
+    Redo,
+}
+
+/// Parses a line of input into a command.
+pub fn parse_command(line: &str) -> Result<Command, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some((name, args)) = words.split_first() else {
+        return Err("Please enter a command, 'help' lists all commands.".to_string());
+    };
+    let expect_args = |count: usize, usage: &str| -> Result<(), String> {
+        match args.len() == count {
+            true => Ok(()),
+            false => Err(format!("'{}' takes {} argument(s): {}", name, count, usage)),
+        }
+    };
+    let number = |text: &str, what: &str| text.parse::<u32>().map_err(|_| format!("'{}' is not a number for {}", text, what));
+    match *name {
+        "dual" => {
+            expect_args(3, "dual <teammate> <their pos> <my pos>")?;
+            Ok(Command::Dual {
+                teammate: number(args[0], "the teammate")?,
+                position_teammate: number(args[1], "the teammate's cable")?,
+                position_self: number(args[2], "your own cable")?,
+            })
+        }
+        "double" => {
+            expect_args(4, "double <teammate> <pos> <pos> <my pos>")?;
+            Ok(Command::Double {
+                teammate: number(args[0], "the teammate")?,
+                positions_teammate: (number(args[1], "the teammate's first cable")?, number(args[2], "the teammate's second cable")?),
+                position_self: number(args[3], "your own cable")?,
+            })
+        }
+        "solo" => {
+            expect_args(1, "solo <value>")?;
+            Ok(Command::Solo { value: parse_value(args[0])? })
+        }
+        "reveal-red" => expect_args(0, "reveal-red").map(|_| Command::RevealRed),
+        "use" => {
+            expect_args(2, "use <equipment> <pos>")?;
+            let equipment = match args[0] {
+                "label-equal" => Equipment::LabelEqual,
+                "label-different" => Equipment::LabelDifferent,
+                other => return Err(format!("'{}' is no equipment, use 'label-equal' or 'label-different'", other)),
+            };
+            Ok(Command::Use { equipment, position: number(args[1], "the cable")? })
+        }
+        "rewind" => expect_args(0, "rewind").map(|_| Command::Rewind),
+        "post-it" => {
+            expect_args(1, "post-it <pos>")?;
+            Ok(Command::PostIt { position: number(args[0], "the cable")? })
+        }
+        "radar" => {
+            expect_args(1, "radar <value>")?;
+            Ok(Command::Radar { value: parse_value(args[0])? })
+        }
+        "view" => expect_args(0, "view").map(|_| Command::View),
+        "help" => expect_args(0, "help").map(|_| Command::Help),
+        "advise" => expect_args(0, "advise").map(|_| Command::Advise),
+        "assist" => expect_args(0, "assist").map(|_| Command::Assist),
+        "save" => expect_args(1, "save <file>").map(|_| Command::Save(args[0].to_string())),
+        "load" => expect_args(1, "load <file>").map(|_| Command::Load(args[0].to_string())),
+        "undo" => expect_args(0, "undo").map(|_| Command::Undo),
+        "redo" => expect_args(0, "redo").map(|_| Command::Redo),
+        other => Err(format!("'{}' is not a command, 'help' lists all commands.", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse_command("dual 2 5 3"), Ok(Command::Dual { teammate: 2, position_teammate: 5, position_self: 3 }));
+        assert_eq!(parse_command("  solo 1.1 "), Ok(Command::Solo { value: 11 }), "Expected surrounding spaces to be ignored");
+        assert_eq!(parse_command("reveal-red"), Ok(Command::RevealRed));
+        assert_eq!(parse_command("double 1 2 3 0"), Ok(Command::Double { teammate: 1, positions_teammate: (2, 3), position_self: 0 }));
+        assert_eq!(parse_command("rewind"), Ok(Command::Rewind));
+        assert_eq!(parse_command("post-it 6"), Ok(Command::PostIt { position: 6 }));
+        assert_eq!(parse_command("radar 4"), Ok(Command::Radar { value: 40 }));
+        assert_eq!(parse_command("use label-equal 4"), Ok(Command::Use { equipment: Equipment::LabelEqual, position: 4 }));
+        assert_eq!(parse_command("save game.json"), Ok(Command::Save("game.json".to_string())));
+        assert_eq!(parse_command("view"), Ok(Command::View));
+    }
+
+    #[test]
+    fn test_parse_command_errors() {
+        assert!(parse_command("").is_err(), "Expected an empty line to be rejected");
+        assert!(parse_command("dual 2 5").unwrap_err().contains("dual <teammate> <their pos> <my pos>"), "Expected the usage of a command with missing arguments");
+        assert!(parse_command("dual two 5 3").unwrap_err().contains("teammate"), "Expected to learn which argument is wrong");
+        assert!(parse_command("solo 1.25").is_err(), "Expected an invalid value to be rejected");
+        assert!(parse_command("use rope 2").is_err(), "Expected unknown equipment to be rejected");
+        assert!(parse_command("double 1 2 0").is_err(), "Expected a double detector cut with one teammate cable to be rejected");
+        assert!(parse_command("jump").is_err(), "Expected an unknown command to be rejected");
+    }
+}
@@ -0,0 +1,339 @@
+//! This module contains a compact copy of a game for fast simulations. Every rack is a handful of bit masks,
+//! bit i of a mask stands for the cable at position i. A `CompactGame` is copied without any allocation,
+//! so playouts can copy and change it as often as they like. It is created from the readable model and can be turned back into it.
+//! Values are stored as codes which keep the order of the values: 1, 1.1, 1.5, 2, 2.1, ...
+
+use std::collections::HashMap;
+
+use crate::elements::get_value;
+use crate::game::{GameOutcome, GameState};
+use crate::player::{CableStatus, Hand, Label, LabelKind, add_announced_cable, add_label, change_cable_status, get_announced_cables, get_cables, get_labels, get_status, init_hands};
+
+pub const MAX_PLAYERS: usize = 5;
+pub const MAX_RACK: usize = 64;
+pub const VALUE_CODES: usize = 40;
+
+/// Returns the code of a cable value, e.g. 0 for 1, 1 for 1.1 and 2 for 1.5.
+pub fn value_code(value: u32) -> Option<usize> {
+    let colour = match value % 10 {
+        0 => 0,
+        1 => 1,
+        5 => 2,
+        _ => return None,
+    };
+    let code = (value / 10).checked_sub(1)? as usize * 3 + colour;
+    (code < VALUE_CODES).then_some(code)
+}
+
+/// Returns the cable value of a code.
+pub fn code_value(code: usize) -> u32 {
+    (code / 3 + 1) as u32 * 10 + [0, 1, 5][code % 3]
+}
+
+pub fn is_red_code(code: usize) -> bool {
+    code % 3 == 2
+}
+
+/// Number of copies of a value in play, if it is in play at all.
+pub fn code_copies(code: usize) -> u32 {
+    if code.is_multiple_of(3) { 4 } else { 1 }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompactRack {
+    pub len: u8,
+    /// Positions of the cables of every value code.
+    pub values: [u64; VALUE_CODES],
+    pub clue: u64,
+    pub cut: u64,
+    pub announced: u64,
+    /// Bit i is set if there is a label between the cables at i and i + 1.
+    pub equal: u64,
+    pub different: u64,
+}
+
+impl CompactRack {
+    fn empty() -> Self {
+        CompactRack { len: 0, values: [0; VALUE_CODES], clue: 0, cut: 0, announced: 0, equal: 0, different: 0 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompactGame {
+    pub players: u8,
+    pub racks: [CompactRack; MAX_PLAYERS],
+    /// Codes of the shown red and yellow values, as a bit mask.
+    pub shown: u64,
+    pub death_counter: u32,
+    pub current_player: u8,
+    pub turn: u32,
+}
+
+/// All positions of a rack.
+pub fn rack_mask(rack: &CompactRack) -> u64 {
+    if rack.len as usize >= MAX_RACK { u64::MAX } else { (1 << rack.len) - 1 }
+}
+
+pub fn uncut_mask(rack: &CompactRack) -> u64 {
+    rack_mask(rack) & !rack.cut
+}
+
+/// Returns the value code of the cable at a position.
+pub fn code_at(rack: &CompactRack, position: usize) -> usize {
+    (0..VALUE_CODES).find(|code| rack.values[*code] & (1 << position) != 0).expect("Every cable has a value")
+}
+
+/// Creates the compact copy of a game. Fails if the game has too many players or cables.
+pub fn compact_game(state: &GameState) -> Result<CompactGame, String> {
+    if state.hands.len() > MAX_PLAYERS {
+        return Err(format!("A compact game has at most {} players", MAX_PLAYERS));
+    }
+    let mut racks = [CompactRack::empty(); MAX_PLAYERS];
+    for (hand, rack) in state.hands.iter().zip(racks.iter_mut()) {
+        let cables = get_cables(hand);
+        if cables.len() > MAX_RACK {
+            return Err(format!("A compact rack has at most {} cables", MAX_RACK));
+        }
+        rack.len = cables.len() as u8;
+        let announced = get_announced_cables(hand);
+        for (i, (id, status)) in cables.iter().zip(get_status(hand)).enumerate() {
+            let value = get_value(*id, &state.all_cables);
+            let code = value_code(value).ok_or(format!("{} is no cable value of a compact game", value))?;
+            rack.values[code] |= 1 << i;
+            match status {
+                CableStatus::Hidden => {}
+                CableStatus::Clue => rack.clue |= 1 << i,
+                CableStatus::Revealed => rack.cut |= 1 << i,
+            }
+            if announced.contains(id) {
+                rack.announced |= 1 << i;
+            }
+        }
+        for label in get_labels(hand) {
+            match label.kind {
+                LabelKind::Equal => rack.equal |= 1 << label.position,
+                LabelKind::Different => rack.different |= 1 << label.position,
+            }
+        }
+    }
+    let mut shown = 0;
+    for value in state.all_cables.values().filter(|value| *value % 10 != 0) {
+        shown |= 1 << value_code(*value).ok_or(format!("{} is no cable value of a compact game", value))?;
+    }
+    Ok(CompactGame {
+        players: state.hands.len() as u8,
+        racks,
+        shown,
+        death_counter: state.death_counter,
+        current_player: state.current_player as u8,
+        turn: state.turn,
+    })
+}
+
+/// Creates readable cables and hands from a compact game. The cable IDs are numbered from 1 in the order of the racks,
+/// followed by the shown red and yellow cables which are not in play.
+// The binary only converts into the compact game so far, the way back is used by the tests.
+#[allow(dead_code)]
+pub fn readable_hands(game: &CompactGame) -> (HashMap<u32, u32>, Vec<Hand>) {
+    let mut all_cables = HashMap::new();
+    let mut distribution = Vec::new();
+    let mut in_play: u64 = 0;
+    let mut next_id = 1;
+    for rack in &game.racks[..game.players as usize] {
+        let mut ids = Vec::new();
+        for position in 0..rack.len as usize {
+            let code = code_at(rack, position);
+            in_play |= 1 << code;
+            all_cables.insert(next_id, code_value(code));
+            ids.push(next_id);
+            next_id += 1;
+        }
+        distribution.push(ids);
+    }
+    for code in (0..VALUE_CODES).filter(|code| game.shown & !in_play & (1 << code) != 0) {
+        all_cables.insert(next_id, code_value(code));
+        next_id += 1;
+    }
+
+    let mut hands = init_hands(distribution.clone());
+    for (player, (rack, ids)) in game.racks.iter().zip(&distribution).enumerate() {
+        for (position, id) in ids.iter().enumerate() {
+            let bit = 1 << position;
+            if rack.cut & bit != 0 {
+                change_cable_status(*id, &mut hands, CableStatus::Revealed);
+            } else if rack.clue & bit != 0 {
+                change_cable_status(*id, &mut hands, CableStatus::Clue);
+            }
+            if rack.announced & bit != 0 {
+                add_announced_cable(&mut hands[player], *id);
+            }
+            if rack.equal & bit != 0 {
+                add_label(&mut hands[player], Label { position, kind: LabelKind::Equal });
+            }
+            if rack.different & bit != 0 {
+                add_label(&mut hands[player], Label { position, kind: LabelKind::Different });
+            }
+        }
+    }
+    (all_cables, hands)
+}
+
+/// Returns the outcome of a compact game, like `get_outcome`.
+pub fn compact_outcome(game: &CompactGame) -> Option<GameOutcome> {
+    let mut all_cut = true;
+    for rack in &game.racks[..game.players as usize] {
+        let red = red_mask(rack);
+        if red & rack.cut != 0 {
+            return Some(GameOutcome::RedCableCut);
+        }
+        if uncut_mask(rack) & !red != 0 {
+            all_cut = false;
+        }
+    }
+    if game.death_counter == 0 {
+        return Some(GameOutcome::DetonatorExpired);
+    }
+    if all_cut {
+        return Some(GameOutcome::Defused);
+    }
+    None
+}
+
+/// Dual cut of the current player, like `play_dual_cut`. Returns whether the cut was successful.
+pub fn compact_dual_cut(game: &mut CompactGame, teammate: usize, position_self: usize, position_teammate: usize) -> Result<bool, String> {
+    let player = game.current_player as usize;
+    if teammate == player || teammate >= game.players as usize {
+        return Err(format!("Player {} cannot cut a cable of player {}", player, teammate));
+    }
+    if uncut_mask(&game.racks[player]) & 1u64.checked_shl(position_self as u32).unwrap_or(0) == 0 {
+        return Err(format!("Player {} has no uncut cable at position {}", player, position_self));
+    }
+    if uncut_mask(&game.racks[teammate]) & 1u64.checked_shl(position_teammate as u32).unwrap_or(0) == 0 {
+        return Err(format!("Player {} has no uncut cable at position {}", teammate, position_teammate));
+    }
+    let code_self = code_at(&game.racks[player], position_self);
+    let code_teammate = code_at(&game.racks[teammate], position_teammate);
+    let success = code_self == code_teammate;
+    if success {
+        cut_cables(&mut game.racks[player], 1 << position_self);
+        cut_cables(&mut game.racks[teammate], 1 << position_teammate);
+    } else if is_red_code(code_teammate) {
+        cut_cables(&mut game.racks[teammate], 1 << position_teammate);
+        game.death_counter = game.death_counter.saturating_sub(1);
+    } else {
+        game.racks[teammate].clue |= 1 << position_teammate;
+        game.racks[player].announced |= 1 << position_self;
+        game.death_counter = game.death_counter.saturating_sub(1);
+    }
+    end_turn(game);
+    Ok(success)
+}
+
+/// Solo cut of the current player, like `play_solo_cut`. Fails if another player still holds an uncut cable of the value.
+pub fn compact_solo_cut(game: &mut CompactGame, code: usize) -> Result<(), String> {
+    let player = game.current_player as usize;
+    if is_red_code(code) {
+        return Err("Red cables cannot be cut".to_string());
+    }
+    for (i, rack) in game.racks[..game.players as usize].iter().enumerate() {
+        let uncut = rack.values[code] & uncut_mask(rack);
+        if i != player && uncut != 0 {
+            return Err(format!("Not all remaining cables with value {} are in player {}'s hand", code_value(code) as f32 / 10.0, player));
+        }
+        if i == player && uncut == 0 {
+            return Err(format!("Player {} has no uncut cable with value {}", player, code_value(code) as f32 / 10.0));
+        }
+    }
+    let cables = game.racks[player].values[code];
+    cut_cables(&mut game.racks[player], cables);
+    end_turn(game);
+    Ok(())
+}
+
+/// The current player reveals their remaining red cables, like `play_reveal_red`.
+pub fn compact_reveal_red(game: &mut CompactGame) -> Result<(), String> {
+    let player = game.current_player as usize;
+    let rack = &mut game.racks[player];
+    let uncut = uncut_mask(rack);
+    if uncut == 0 || uncut & !red_mask(rack) != 0 {
+        return Err(format!("Player {} still has cables which are not red", player));
+    }
+    rack.clue |= uncut;
+    end_turn(game);
+    Ok(())
+}
+
+/// Positions of the red cables of a rack.
+fn red_mask(rack: &CompactRack) -> u64 {
+    (0..VALUE_CODES).filter(|code| is_red_code(*code)).fold(0, |mask, code| mask | rack.values[code])
+}
+
+/// Cuts the cables of a mask. A cut cable is no clue anymore.
+fn cut_cables(rack: &mut CompactRack, cables: u64) {
+    rack.cut |= cables;
+    rack.clue &= !cables;
+}
+
+fn end_turn(game: &mut CompactGame) {
+    let players = game.players;
+    game.current_player = (1..=players)
+        .map(|step| (game.current_player + step) % players)
+        .find(|candidate| {
+            // Like `next_player`: a hidden cable or a clue which is not red is left.
+            let rack = &game.racks[*candidate as usize];
+            uncut_mask(rack) & !(rack.clue & red_mask(rack)) != 0
+        })
+        .unwrap_or(game.current_player);
+    game.turn += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::new_game;
+    use crate::events::play_dual_cut;
+    use crate::game::{GameMeta, get_outcome};
+
+    #[test]
+    fn test_value_codes() {
+        for value in [10, 11, 15, 20, 75, 120] {
+            assert_eq!(code_value(value_code(value).unwrap()), value, "Expected {} to survive the conversion", value);
+        }
+        assert!(value_code(10) < value_code(11) && value_code(11) < value_code(15) && value_code(15) < value_code(20), "Expected codes to keep the order of values");
+        assert_eq!(value_code(13), None, "Expected no code for an unknown colour");
+    }
+
+    #[test]
+    fn test_conversion_round_trip() {
+        let mut state = new_game(GameMeta::new(10, 2, 1, 3, 2, 3), None, 17);
+        play_dual_cut(&mut state, 1, 2, 4).unwrap();
+        let game = compact_game(&state).unwrap();
+        let (all_cables, hands) = readable_hands(&game);
+        assert_eq!(all_cables.len(), state.all_cables.len(), "Expected every cable to be kept");
+        for (hand, original) in hands.iter().zip(&state.hands) {
+            let values: Vec<u32> = get_cables(hand).iter().map(|id| all_cables[id]).collect();
+            let original_values: Vec<u32> = get_cables(original).iter().map(|id| state.all_cables[id]).collect();
+            assert_eq!(values, original_values, "Expected the values to be kept");
+            assert_eq!(get_status(hand), get_status(original), "Expected the statuses to be kept");
+            assert_eq!(get_labels(hand), get_labels(original), "Expected the labels to be kept");
+            assert_eq!(get_announced_cables(hand).len(), get_announced_cables(original).len(), "Expected the claims to be kept");
+        }
+    }
+
+    #[test]
+    fn test_compact_dual_cut_matches_game() {
+        let mut state = new_game(GameMeta::new(8, 1, 1, 0, 0, 2), None, 4);
+        let mut game = compact_game(&state).unwrap();
+        for position_teammate in 0..3 {
+            let teammate = 1 - state.current_player;
+            let position_self = get_status(&state.hands[state.current_player as usize]).iter().position(|s| *s != CableStatus::Revealed).unwrap();
+            let success = play_dual_cut(&mut state, teammate, position_self as u32, position_teammate).unwrap();
+            let compact_success = compact_dual_cut(&mut game, teammate as usize, position_self, position_teammate as usize).unwrap();
+            assert_eq!(compact_success, success, "Expected the same result of the cut");
+            assert_eq!(compact_game(&state).unwrap(), game, "Expected the same racks after the cut");
+            assert_eq!(compact_outcome(&game), get_outcome(&state.hands, &state.all_cables, state.death_counter), "Expected the same outcome");
+        }
+        let player = game.current_player as usize;
+        assert!(compact_dual_cut(&mut game, player, 0, 0).is_err(), "Expected a cut of the own rack to be rejected");
+    }
+}
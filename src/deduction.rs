@@ -0,0 +1,377 @@
+//! This module deduces what the hidden cables of the teammates can be, from the view of one player.
+//! Racks are sorted by value, so every visible cable bounds its hidden neighbours. Together with the remaining
+//! copies of every value, the claims of the players, the labels and the number of red and yellow cables in play,
+//! the possible values of every hidden cable are narrowed down until nothing changes anymore.
+//! Every value that is ruled out is certainly impossible, but not every remaining value has to fit into a complete deal.
+
+use std::collections::HashMap;
+
+use crate::player::LabelKind;
+use crate::view::PlayerView;
+
+/// Possible values for every cable, indexed by player and position. Visible cables have exactly one possible value.
+pub type Possibilities = Vec<Vec<Vec<u32>>>;
+
+/// Shown red or yellow values with whether they are in play.
+pub type InPlayValues = Vec<(u32, InPlay)>;
+
+/// Returns every value of the game with its number of copies. Each red and yellow value exists once.
+pub fn value_copies(view: &PlayerView) -> HashMap<u32, u32> {
+    let mut copies: HashMap<u32, u32> = (1..=view.meta.blue_max).map(|i| (i * 10, 4)).collect();
+    for value in view.red_values.iter().chain(&view.yellow_values) {
+        copies.insert(*value, 1);
+    }
+    copies
+}
+
+/// Returns how many copies of every value the player cannot see.
+pub fn remaining_copies(view: &PlayerView) -> HashMap<u32, u32> {
+    let mut remaining = value_copies(view);
+    for rack in &view.racks {
+        for value in rack.slots.iter().filter_map(|slot| slot.value) {
+            if let Some(count) = remaining.get_mut(&value) {
+                *count = count.saturating_sub(1);
+            }
+        }
+    }
+    remaining
+}
+
+/// Returns how many hidden cables of every value each player must hold because of their claims.
+/// A claim which is matched by a visible uncut cable of the same player does not need a hidden cable.
+pub fn claimed_hidden(view: &PlayerView) -> Vec<HashMap<u32, u32>> {
+    view.racks.iter().map(|rack| {
+        let mut claimed: HashMap<u32, u32> = HashMap::new();
+        for value in &rack.claims {
+            *claimed.entry(*value).or_insert(0) += 1;
+        }
+        for slot in &rack.slots {
+            if let (Some(value), crate::player::CableStatus::Clue) = (slot.value, slot.status)
+                && let Some(count) = claimed.get_mut(&value)
+            {
+                *count = count.saturating_sub(1);
+            }
+        }
+        if rack.slots.iter().all(|slot| slot.value.is_some()) {
+            claimed.clear();
+        }
+        claimed
+    }).collect()
+}
+
+/// How many red and yellow cables may still be hidden, besides the visible ones.
+fn hidden_colour_limits(view: &PlayerView) -> (u32, u32) {
+    let visible = |values: &Vec<u32>| -> u32 {
+        view.racks.iter()
+            .flat_map(|rack| rack.slots.iter().filter_map(|slot| slot.value))
+            .filter(|value| values.contains(value))
+            .count() as u32
+    };
+    (
+        view.meta.red_keep.saturating_sub(visible(&view.red_values)),
+        view.meta.yellow_keep.saturating_sub(visible(&view.yellow_values)),
+    )
+}
+
+/// Computes the possible values of every cable from the view of a player.
+pub fn possible_values(view: &PlayerView) -> Possibilities {
+    let remaining = remaining_copies(view);
+    let claimed = claimed_hidden(view);
+    let (red_left, yellow_left) = hidden_colour_limits(view);
+    let allowed = |value: &u32| -> bool {
+        remaining[value] > 0
+            && !(view.red_values.contains(value) && red_left == 0)
+            && !(view.yellow_values.contains(value) && yellow_left == 0)
+    };
+    let mut candidates: Vec<u32> = remaining.keys().copied().filter(allowed).collect();
+    candidates.sort();
+
+    let mut possible: Possibilities = view.racks.iter()
+        .map(|rack| rack.slots.iter().map(|slot| match slot.value {
+            Some(value) => vec![value],
+            None => candidates.clone(),
+        }).collect())
+        .collect();
+    let hidden: Vec<Vec<bool>> = view.racks.iter()
+        .map(|rack| rack.slots.iter().map(|slot| slot.value.is_none()).collect())
+        .collect();
+
+    loop {
+        let before = possible.clone();
+        for (r, rack) in view.racks.iter().enumerate() {
+            narrow_by_order(&mut possible[r]);
+            for label in &rack.labels {
+                narrow_by_label(&mut possible[r], label.position, label.kind);
+            }
+            narrow_by_claims(&mut possible[r], &hidden[r], &claimed[r]);
+        }
+        for (value, count) in &remaining {
+            narrow_by_copies(&mut possible, &hidden, &claimed, &[*value], *count);
+        }
+        narrow_by_copies(&mut possible, &hidden, &claimed, &view.red_values, red_left);
+        narrow_by_copies(&mut possible, &hidden, &claimed, &view.yellow_values, yellow_left);
+        if possible == before {
+            return possible;
+        }
+    }
+}
+
+/// Whether a shown red or yellow value is in the game, as far as a player can tell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InPlay {
+    Yes,
+    No,
+    Maybe,
+}
+
+/// Infers for every shown red and yellow value whether it is in play. A value is in play if its cable is visible
+/// or some hidden cable can only have this value, and out of play if no hidden cable can have it anymore.
+/// If the values which are not out of play are exactly as many as are kept, they are all in play.
+pub fn cables_in_play(view: &PlayerView, possible: &Possibilities) -> (InPlayValues, InPlayValues) {
+    let remaining = remaining_copies(view);
+    let infer = |values: &Vec<u32>, keep: u32| -> Vec<(u32, InPlay)> {
+        let mut in_play: Vec<(u32, InPlay)> = values.iter().map(|value| {
+            let hidden_slots = view.racks.iter().enumerate()
+                .flat_map(|(r, rack)| rack.slots.iter().enumerate().filter(|(_, slot)| slot.value.is_none()).map(move |(i, _)| &possible[r][i]));
+            let state = if remaining[value] == 0 {
+                InPlay::Yes
+            } else {
+                let mut can = false;
+                let mut must = false;
+                for slot in hidden_slots {
+                    can |= slot.contains(value);
+                    must |= slot[..] == [*value];
+                }
+                match (must, can) {
+                    (true, _) => InPlay::Yes,
+                    (false, true) => InPlay::Maybe,
+                    (false, false) => InPlay::No,
+                }
+            };
+            (*value, state)
+        }).collect();
+        let not_out = in_play.iter().filter(|(_, state)| *state != InPlay::No).count() as u32;
+        let certain = in_play.iter().filter(|(_, state)| *state == InPlay::Yes).count() as u32;
+        for (_, state) in in_play.iter_mut() {
+            if *state == InPlay::Maybe && not_out == keep {
+                *state = InPlay::Yes;
+            } else if *state == InPlay::Maybe && certain == keep {
+                *state = InPlay::No;
+            }
+        }
+        in_play
+    };
+    (infer(&view.red_values, view.meta.red_keep), infer(&view.yellow_values, view.meta.yellow_keep))
+}
+
+/// Returns the lowest and highest possible value of a cable.
+pub fn value_range(possible: &[u32]) -> Option<(u32, u32)> {
+    Some((*possible.iter().min()?, *possible.iter().max()?))
+}
+
+/// A rack is sorted: no cable can be lower than the lowest possible value of its left neighbour,
+/// nor higher than the highest possible value of its right neighbour.
+fn narrow_by_order(rack: &mut [Vec<u32>]) {
+    let mut low = 0;
+    for slot in rack.iter_mut() {
+        slot.retain(|value| *value >= low);
+        if let Some(min) = slot.iter().min() {
+            low = *min;
+        }
+    }
+    let mut high = u32::MAX;
+    for slot in rack.iter_mut().rev() {
+        slot.retain(|value| *value <= high);
+        if let Some(max) = slot.iter().max() {
+            high = *max;
+        }
+    }
+}
+
+fn narrow_by_label(rack: &mut [Vec<u32>], position: usize, kind: LabelKind) {
+    if position + 1 >= rack.len() {
+        return;
+    }
+    match kind {
+        LabelKind::Equal => {
+            let common: Vec<u32> = rack[position].iter().filter(|value| rack[position + 1].contains(value)).copied().collect();
+            rack[position] = common.clone();
+            rack[position + 1] = common;
+        }
+        LabelKind::Different => {
+            if let [value] = rack[position][..] {
+                rack[position + 1].retain(|v| *v != value);
+            }
+            if let [value] = rack[position + 1][..] {
+                rack[position].retain(|v| *v != value);
+            }
+        }
+    }
+}
+
+/// A player who claimed a value holds it: if only as many hidden cables can have this value as were claimed, they all have it.
+fn narrow_by_claims(rack: &mut [Vec<u32>], hidden: &[bool], claimed: &HashMap<u32, u32>) {
+    for (value, count) in claimed {
+        if *count == 0 {
+            continue;
+        }
+        let slots: Vec<usize> = (0..rack.len()).filter(|i| hidden[*i] && rack[*i].contains(value)).collect();
+        if slots.len() as u32 <= *count {
+            for i in slots {
+                rack[i] = vec![*value];
+            }
+        }
+    }
+}
+
+/// At most `limit` hidden cables have one of `values`. Cables fixed to one of the values and claimed values use up the limit,
+/// once it is reached no other hidden cable can have one of the values.
+fn narrow_by_copies(possible: &mut Possibilities, hidden: &[Vec<bool>], claimed: &[HashMap<u32, u32>], values: &[u32], limit: u32) {
+    if values.is_empty() {
+        return;
+    }
+    // Hidden cables fixed to each of the values, per rack.
+    let fixed: Vec<HashMap<u32, u32>> = possible.iter().enumerate().map(|(r, rack)| {
+        values.iter().map(|value| {
+            let count = rack.iter().enumerate().filter(|(i, slot)| hidden[r][*i] && slot[..] == [*value]).count() as u32;
+            (*value, count)
+        }).collect()
+    }).collect();
+    // Every rack certainly holds its fixed cables or its claims of a value, whichever are more.
+    let committed: u32 = fixed.iter().enumerate()
+        .map(|(r, counts)| counts.iter().map(|(value, count)| (*count).max(*claimed[r].get(value).unwrap_or(&0))).sum::<u32>())
+        .sum();
+
+    for (r, rack) in possible.iter_mut().enumerate() {
+        for (i, slot) in rack.iter_mut().enumerate() {
+            if !hidden[r][i] || slot.len() <= 1 {
+                continue;
+            }
+            slot.retain(|value| {
+                if !values.contains(value) {
+                    return true;
+                }
+                // A claimed value which is not placed yet is already counted.
+                let unplaced_claim = claimed[r].get(value).unwrap_or(&0) > &fixed[r][value];
+                unplaced_claim || committed < limit
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::state_with_hands;
+    use std::collections::HashMap;
+    use crate::game::GameMeta;
+    use crate::player::{CableStatus, Label, add_announced_cable, add_label, change_cable_status};
+    use crate::view::player_view;
+
+    fn blue_cables(blue_max: u32) -> HashMap<u32, u32> {
+        (0..blue_max * 4).map(|i| (i + 1, (i / 4 + 1) * 10)).collect()
+    }
+
+    #[test]
+    fn test_order_and_copies() {
+        // Values 1, 2 and 3 with four copies each. Player 0 holds both 1s and 2s of the first half.
+        let all_cables = blue_cables(3);
+        let mut state = state_with_hands(GameMeta::new(3, 0, 0, 0, 0, 2), all_cables, vec![
+            vec![1, 2, 5, 6, 9, 10],
+            vec![3, 4, 7, 8, 11, 12],
+        ]);
+        change_cable_status(7, &mut state.hands, CableStatus::Revealed);
+
+        let possible = possible_values(&player_view(&state, 0));
+        assert_eq!(possible[0][0], vec![10], "Expected own cables to be known");
+        assert_eq!(possible[1][2], vec![20], "Expected the revealed cable to be known");
+        assert_eq!(possible[1][0], vec![10, 20], "Expected cables left of a 2 to be at most 2");
+        assert_eq!(possible[1][5], vec![20, 30], "Expected cables right of a 2 to be at least 2");
+        assert_eq!(possible[1][3], vec![20, 30], "Expected the neighbour of a 2 to be at least 2");
+    }
+
+    #[test]
+    fn test_all_copies_visible() {
+        let all_cables = blue_cables(2);
+        let state = state_with_hands(GameMeta::new(2, 0, 0, 0, 0, 2), all_cables, vec![
+            vec![5, 6, 7, 8],
+            vec![1, 2, 3, 4],
+        ]);
+        let possible = possible_values(&player_view(&state, 0));
+        for slot in &possible[1] {
+            assert_eq!(slot, &vec![10], "Expected all 2s to be ruled out when the player holds all of them");
+        }
+    }
+
+    #[test]
+    fn test_labels_and_claims() {
+        let all_cables = blue_cables(3);
+        let mut state = state_with_hands(GameMeta::new(3, 0, 0, 0, 0, 2), all_cables, vec![
+            vec![1, 5, 6, 9, 10, 11],
+            vec![2, 3, 4, 7, 8, 12],
+        ]);
+        // Player 1 shows a 1 on the first cable and labels it as different from the second one.
+        change_cable_status(2, &mut state.hands, CableStatus::Clue);
+        add_label(&mut state.hands[1], Label { position: 0, kind: LabelKind::Different });
+        let possible = possible_values(&player_view(&state, 0));
+        assert!(!possible[1][1].contains(&10), "Expected the ≠ label to rule out a 1 next to the shown 1");
+
+        // Player 1 claims a 3: only the last cable can still be a 3.
+        add_label(&mut state.hands[1], Label { position: 1, kind: LabelKind::Equal });
+        add_announced_cable(&mut state.hands[1], 12);
+        let possible = possible_values(&player_view(&state, 0));
+        assert_eq!(possible[1][1], possible[1][2], "Expected the = label to give both cables the same possible values");
+        assert!(possible[1][5].contains(&30), "Expected the claimed value to stay possible");
+    }
+
+    #[test]
+    fn test_red_cables_in_play() {
+        let mut all_cables = blue_cables(2);
+        all_cables.insert(9, 15);
+        all_cables.insert(10, 25);
+        let mut state = state_with_hands(GameMeta::new(2, 2, 1, 0, 0, 2), all_cables, vec![
+            vec![1, 2, 3, 4],
+            vec![9, 5, 6, 7, 8],
+        ]);
+        let possible = possible_values(&player_view(&state, 0));
+        assert!(possible[1][0].contains(&15) && possible[1][0].contains(&25), "Expected both red values to be possible while none is visible");
+
+        change_cable_status(9, &mut state.hands, CableStatus::Clue);
+        let possible = possible_values(&player_view(&state, 0));
+        for slot in &possible[1][1..] {
+            assert!(!slot.contains(&25) && !slot.contains(&15), "Expected no further red cable once the only red cable in play is visible");
+        }
+    }
+
+    #[test]
+    fn test_cables_in_play() {
+        let mut all_cables = blue_cables(3);
+        all_cables.insert(13, 15);
+        all_cables.insert(14, 25);
+        all_cables.insert(15, 35);
+        // Red cables 1.5 and 2.5 are in play, 3.5 is not.
+        let mut state = state_with_hands(GameMeta::new(3, 3, 2, 0, 0, 2), all_cables, vec![
+            vec![1, 2, 13, 5, 6, 9, 10],
+            vec![3, 4, 7, 8, 14, 11, 12],
+        ]);
+        let view = player_view(&state, 0);
+        let (red, yellow) = cables_in_play(&view, &possible_values(&view));
+        assert!(yellow.is_empty(), "Expected no yellow cables");
+        assert_eq!(red[0], (15, InPlay::Yes), "Expected the own red cable to be in play");
+        assert_eq!(red[1], (25, InPlay::Maybe), "Expected 2.5 to be uncertain");
+        assert_eq!(red[2], (35, InPlay::Maybe), "Expected 3.5 to be uncertain");
+
+        // Player 1 shows a 3 on the last cable, so no cable can be 3.5 and the second red cable must be 2.5.
+        change_cable_status(12, &mut state.hands, CableStatus::Clue);
+        let view = player_view(&state, 0);
+        let (red, _) = cables_in_play(&view, &possible_values(&view));
+        assert_eq!(red[2], (35, InPlay::No), "Expected 3.5 to be ruled out by the rack order");
+        assert_eq!(red[1], (25, InPlay::Yes), "Expected 2.5 to be the remaining red cable in play");
+    }
+
+    #[test]
+    fn test_value_range() {
+        assert_eq!(value_range(&[50, 60, 70]), Some((50, 70)));
+        assert_eq!(value_range(&[]), None);
+    }
+}
@@ -0,0 +1,73 @@
+//! This module estimates how hard a game configuration is. Heuristic bots play a fixed set of seeds,
+//! so the same configuration always gets the same rating, and their win rate is placed on a rating scale.
+
+use crate::game::GameMeta;
+use crate::mission::Mission;
+use crate::montecarlo::Budget;
+use crate::seat::Seat;
+use crate::simulation::{SimulationReport, default_threads, run_simulation, win_rate};
+
+/// Number of bot games behind an estimate.
+pub const DIFFICULTY_GAMES: u64 = 20;
+
+/// Rating scale of a configuration, from the easiest to the hardest.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Expert,
+    Insane,
+}
+
+/// Rates a win rate of the bots: the less often they defuse the bomb, the harder the configuration.
+pub fn rate_win_rate(win_rate: f64) -> Difficulty {
+    match win_rate {
+        rate if rate >= 0.9 => Difficulty::Easy,
+        rate if rate >= 0.7 => Difficulty::Normal,
+        rate if rate >= 0.5 => Difficulty::Hard,
+        rate if rate >= 0.25 => Difficulty::Expert,
+        _ => Difficulty::Insane,
+    }
+}
+
+/// Short description of a rating for the setup wizard.
+pub fn describe_difficulty(difficulty: Difficulty) -> &'static str {
+    match difficulty {
+        Difficulty::Easy => "a relaxed game for new groups",
+        Difficulty::Normal => "a fair challenge",
+        Difficulty::Hard => "the bomb explodes about every other game",
+        Difficulty::Expert => "only careful groups defuse this bomb",
+        Difficulty::Insane => "the bomb explodes most of the time",
+    }
+}
+
+/// Estimates the difficulty of a configuration from `games` games of heuristic bots.
+pub fn estimate_difficulty(meta: &GameMeta, mission: &Option<Mission>, games: u64) -> Result<(Difficulty, SimulationReport), String> {
+    let seats = vec![Seat::Bot; meta.players as usize];
+    let report = run_simulation(meta, mission, &seats, 0..games, default_threads(), Budget::default())?;
+    Ok((rate_win_rate(win_rate(&report)), report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_win_rate() {
+        assert_eq!(rate_win_rate(1.0), Difficulty::Easy, "Expected bots which always win to rate easy");
+        assert_eq!(rate_win_rate(0.7), Difficulty::Normal, "Expected the lower bound to belong to the rating");
+        assert_eq!(rate_win_rate(0.3), Difficulty::Expert);
+        assert_eq!(rate_win_rate(0.0), Difficulty::Insane, "Expected bots which never win to rate insane");
+        assert!(rate_win_rate(0.2) > rate_win_rate(0.8), "Expected lower win rates to rate harder");
+    }
+
+    #[test]
+    fn test_estimate_difficulty() {
+        let meta = GameMeta::new(6, 1, 1, 0, 0, 2);
+        let (difficulty, report) = estimate_difficulty(&meta, &None, 2).unwrap();
+        assert_eq!(report.games, 2, "Expected one game per requested game");
+        assert_eq!(difficulty, rate_win_rate(win_rate(&report)), "Expected the rating to follow the win rate");
+        assert_eq!(estimate_difficulty(&meta, &None, 2).unwrap(), (difficulty, report), "Expected the same estimate for the same configuration");
+    }
+}
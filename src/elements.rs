@@ -3,13 +3,10 @@
 //! The collections should be initialized once immutably at the start of the game with the init functions. Other functions refer to these collections by unique IDs.
 
 use std::collections::{HashMap, HashSet};
-use std::sync::atomic::{AtomicU32, Ordering};
 use rand::prelude::*;
-use rust_helpers::{split_rand_vec, split_rand_vec_eq};
+use serde::{Deserialize, Serialize};
 
-static CABLE_ID_COUNTER: AtomicU32 = AtomicU32::new(1);
-
-/// Initializes all cables with a given configuration.
+/// Initializes all cables with a given configuration. The IDs of every game start at 1.
 ///
 /// # Arguments
 /// * `blue_max_val` - The maximum value for blue cables. There are 4 cables for each value.
@@ -20,26 +17,32 @@ static CABLE_ID_COUNTER: AtomicU32 = AtomicU32::new(1);
 /// A `HashMap` where the keys are cable IDs and the values are the cable values.
 pub fn init_all_cables(blue_max_val: u32, red_show: u32, yellow_show: u32) -> HashMap<u32, u32> {
     let mut cables = HashMap::new();
+    let mut next_id = 1;
+    let mut insert = |value: u32| {
+        cables.insert(next_id, value);
+        next_id += 1;
+    };
     for i in 1..=blue_max_val {
         for _ in 1..=4 {
-            cables.insert(CABLE_ID_COUNTER.fetch_add(1, Ordering::Relaxed), i as u32 * 10);
+            insert(i * 10);
         }
     }
     for j in 1..=red_show {
-        cables.insert(CABLE_ID_COUNTER.fetch_add(1, Ordering::Relaxed), j as u32 * 10 + 5);
+        insert(j * 10 + 5);
     }
     for k in 1..=yellow_show {
-        cables.insert(CABLE_ID_COUNTER.fetch_add(1, Ordering::Relaxed), k as u32 * 10 + 1);
+        insert(k * 10 + 1);
     }
-    println!("All cables initialized.");
 
-    return cables
+    cables
 }
 
+/// Selects the cables which are used in the game: all blue cables, `red_keep` of the red and `yellow_keep` of the yellow cables.
 pub fn init_cables_in_game(
     cables: &HashMap<u32, u32>,
     red_keep: u32,
     yellow_keep: u32,
+    rng: &mut impl Rng,
 ) -> HashSet<u32> {
     let mut in_game_cables: HashSet<u32> = HashSet::new();
     let blue_cable_ids: Vec<u32> = cables
@@ -49,25 +52,24 @@ pub fn init_cables_in_game(
         .collect();
     in_game_cables.extend(blue_cable_ids);
 
-    let mut rng = rand::rng();
-    
-    let red_cable_ids: Vec<u32> = cables
+    // The IDs are sorted before choosing, so that the same RNG state always selects the same cables.
+    let mut red_cable_ids: Vec<u32> = cables
         .iter()
         .filter(|(_, v)| *v % 10 == 5)
-        .choose_multiple(&mut rng, red_keep as usize).into_iter()
         .map(|(k, _)| *k)
         .collect();
-    in_game_cables.extend(red_cable_ids);
+    red_cable_ids.sort();
+    in_game_cables.extend(red_cable_ids.choose_multiple(rng, red_keep as usize));
 
-    let yellow_cable_ids: Vec<u32> = cables
+    let mut yellow_cable_ids: Vec<u32> = cables
         .iter()
         .filter(|(_, v)| *v % 10 == 1)
-        .choose_multiple(&mut rng, yellow_keep as usize).into_iter()
         .map(|(k, _)| *k)
         .collect();
-    in_game_cables.extend(yellow_cable_ids);
+    yellow_cable_ids.sort();
+    in_game_cables.extend(yellow_cable_ids.choose_multiple(rng, yellow_keep as usize));
     
-    return in_game_cables
+    in_game_cables
 
 }
 
@@ -75,7 +77,7 @@ pub fn get_yel_red_info(all_cables: &HashMap<u32, u32>) -> (HashSet<u32>, HashSe
     let mut red_cables: HashSet<u32> = HashSet::new();
     let mut yellow_cables: HashSet<u32> = HashSet::new();
     
-    for (cable_id, cable_value) in all_cables.iter() {
+    for cable_value in all_cables.values() {
         if cable_value % 10 == 5 {
             red_cables.insert(*cable_value);
         } else if cable_value % 10 == 1 {
@@ -83,16 +85,32 @@ pub fn get_yel_red_info(all_cables: &HashMap<u32, u32>) -> (HashSet<u32>, HashSe
         }
     }
     
-    return (red_cables, yellow_cables)
+    (red_cables, yellow_cables)
+}
+
+/// Equipment cards the team can use during the game. Each card can be used once.
+#[derive(Clone, Debug, PartialEq, Copy, Serialize, Deserialize)]
+pub enum Equipment {
+    /// Marks two adjacent cables in the user's own hand as having the same value.
+    LabelEqual,
+    /// Marks two adjacent cables in the user's own hand as having different values.
+    LabelDifferent,
 }
 
+/// All equipment cards, used when no mission restricts the equipment.
+pub const ALL_EQUIPMENT: [Equipment; 2] = [Equipment::LabelEqual, Equipment::LabelDifferent];
+
 /// When the counter reaches zero, the bomb explodes. The more player, the higher the initial value.
 pub fn init_death_counter(number_players: u32) -> u32 {
-    return number_players + 1 
+    number_players + 1 
 }
 
 pub fn get_color(cable_id: u32, all_cables: &HashMap<u32, u32>) -> String {
     let cable_value = all_cables.get(&cable_id).expect("Cable ID not found");
+    get_value_color(*cable_value)
+}
+
+pub fn get_value_color(cable_value: u32) -> String {
     match cable_value % 10 {
         0 => "blue".to_string(),
         5 => "red".to_string(),
@@ -125,17 +143,19 @@ mod tests {
         assert_eq!(num_blue_cables, 4 * blue_max_val as usize, "Expected {} blue cables, found {}", 4 * blue_max_val, num_blue_cables);
         assert_eq!(num_red_cables, red_show as usize, "Expected {} red cables, found {}", red_show, num_red_cables);
         assert_eq!(num_yellow_cables, yellow_show as usize, "Expected {} yellow cables, found {}", yellow_show, num_yellow_cables);
+        assert!((1..=cables.len() as u32).all(|id| cables.contains_key(&id)), "Expected the IDs to be numbered from 1");
+        assert_eq!(init_all_cables(blue_max_val, red_show, yellow_show), cables, "Expected every game to get the same IDs");
     }
 
     #[test]
     fn test_init_cables_in_game() {
         let cables = init_all_cables(12, 2, 3);
         let (red_keep, yellow_keep) = (1, 2);
-        let in_game_cables = init_cables_in_game(&cables, red_keep, yellow_keep);
+        let in_game_cables = init_cables_in_game(&cables, red_keep, yellow_keep, &mut rand::rng());
         let expected_cables = 4 * 12 + red_keep + yellow_keep;
         assert_eq!(in_game_cables.len(), expected_cables as usize, "Expected {} cables in game, found {}", expected_cables, in_game_cables.len());
 
-        let num_blue_cables = in_game_cables.iter().filter(|&&id| cables[&id] % 10 == 0).count();
+        let num_blue_cables = in_game_cables.iter().filter(|&&id| cables[&id].is_multiple_of(10)).count();
         let num_red_cables = in_game_cables.iter().filter(|&&id| cables[&id] % 10 == 5).count();
         let num_yellow_cables = in_game_cables.iter().filter(|&&id| cables[&id] % 10 == 1).count();
         assert_eq!(num_blue_cables, 4 * 12, "Expected {} blue cables in game, found {}", 4 * 12, num_blue_cables);
@@ -0,0 +1,435 @@
+//! This module contains the game log. Every change of the game state is an event which is applied to the state
+//! and appended to its log. Replaying the events of a log from the start rebuilds the game state exactly.
+//! Events only describe what was done, their effects are computed by the functions in `actions`.
+
+use std::collections::HashMap;
+
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::actions::*;
+use crate::character::Character;
+use crate::game::{GameMeta, GameState, available_equipment, label_equipment, next_player};
+use crate::mission::Mission;
+use crate::player::{CableStatus, Hand, LabelKind, change_cable_status, get_cables, get_status, init_hands};
+
+// The deal is by far the largest event, but a log holds only one.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    /// The game was set up. Contains everything needed to rebuild the state before the first turn,
+    /// including the state of the RNG after dealing.
+    Deal {
+        meta: GameMeta,
+        mission: Option<Mission>,
+        seed: u64,
+        rng: ChaCha8Rng,
+        all_cables: HashMap<u32, u32>,
+        hands: Vec<Vec<u32>>,
+        characters: Vec<Character>,
+        death_counter: u32,
+    },
+    /// A player placed their info token on one of their cables at the start of the game.
+    TokenPlaced { player: u32, position: u32 },
+    DualCut { player: u32, teammate: u32, position_self: u32, position_teammate: u32 },
+    DoubleDetectorCut { player: u32, teammate: u32, position_self: u32, positions_teammate: (u32, u32) },
+    SoloCut { player: u32, value: u32 },
+    LabelPlaced { player: u32, position: u32, kind: LabelKind },
+    Rewound { player: u32 },
+    PostIt { player: u32, position: u32 },
+    GeneralRadar { player: u32, value: u32 },
+    /// A player with only red cables left turned them face up.
+    RedRevealed { player: u32 },
+    /// The detonator moved one step closer to zero.
+    DetonatorTick,
+    TurnEnded,
+}
+
+/// Creates the state of a game right after the deal.
+pub fn state_from_deal(event: &GameEvent) -> Result<GameState, String> {
+    let GameEvent::Deal { meta, mission, seed, rng, all_cables, hands, characters, death_counter } = event else {
+        return Err("A game log has to start with the deal".to_string());
+    };
+    Ok(GameState {
+        meta: meta.clone(),
+        mission: mission.clone(),
+        seed: *seed,
+        rng: rng.clone(),
+        all_cables: all_cables.clone(),
+        hands: init_hands(hands.clone()),
+        characters: characters.clone(),
+        death_counter: *death_counter,
+        current_player: 0,
+        turn: 1,
+        log: vec![event.clone()],
+        redo: vec![],
+    })
+}
+
+/// Applies an event to the game state without recording it. Fails without changing the state if the event is not allowed.
+pub fn apply_event(state: &mut GameState, event: &GameEvent) -> Result<(), String> {
+    match *event {
+        GameEvent::Deal { .. } => return Err("The game has already been dealt".to_string()),
+        GameEvent::TokenPlaced { player, position } => {
+            check_uncut(state, player, position)?;
+            check_token(state, player)?;
+            let cable_id = get_cables(&state.hands[player as usize])[position as usize];
+            change_cable_status(cable_id, &mut state.hands, CableStatus::Clue);
+        }
+        GameEvent::DualCut { player, teammate, position_self, position_teammate } => {
+            check_turn(state, player)?;
+            check_teammate(state, player, teammate)?;
+            check_uncut(state, player, position_self)?;
+            check_uncut(state, teammate, position_teammate)?;
+            duo_cut(player, teammate, position_self, position_teammate, &mut state.hands, &state.all_cables);
+        }
+        GameEvent::DoubleDetectorCut { player, teammate, position_self, positions_teammate } => {
+            check_turn(state, player)?;
+            check_teammate(state, player, teammate)?;
+            check_uncut(state, player, position_self)?;
+            check_uncut(state, teammate, positions_teammate.0)?;
+            check_uncut(state, teammate, positions_teammate.1)?;
+            double_detector_cut(player, teammate, position_self, positions_teammate, &mut state.hands, &state.all_cables, &mut state.characters[player as usize])?;
+        }
+        GameEvent::SoloCut { player, value } => {
+            check_turn(state, player)?;
+            solo_cut(player, value, &mut state.hands, &state.all_cables)?;
+        }
+        GameEvent::LabelPlaced { player, position, kind } => {
+            check_player(state, player)?;
+            if !available_equipment(state).contains(&label_equipment(kind)) {
+                return Err(format!("The {:?} equipment is not available", label_equipment(kind)));
+            }
+            use_label(player, position as usize, kind, &mut state.hands, &state.all_cables)?;
+        }
+        GameEvent::Rewound { player } => {
+            check_player(state, player)?;
+            use_rewinder(&mut state.death_counter, &mut state.characters[player as usize])?;
+        }
+        GameEvent::PostIt { player, position } => {
+            check_uncut(state, player, position)?;
+            use_post_it(player, position, &mut state.hands, &mut state.characters[player as usize])?;
+        }
+        GameEvent::GeneralRadar { player, value } => {
+            check_player(state, player)?;
+            use_general_radar(value, &state.hands, &state.all_cables, &mut state.characters[player as usize])?;
+        }
+        GameEvent::RedRevealed { player } => {
+            check_turn(state, player)?;
+            reveal_red(player, &mut state.hands, &state.all_cables)?;
+        }
+        GameEvent::DetonatorTick => {
+            if !state.log.last().is_some_and(|last| is_failed_cut(state, last)) {
+                return Err("The detonator only ticks after a failed cut".to_string());
+            }
+            state.death_counter = state.death_counter.saturating_sub(1);
+        }
+        GameEvent::TurnEnded => {
+            match state.log.last() {
+                Some(last) if is_failed_cut(state, last) => return Err("The detonator has to tick before the turn ends".to_string()),
+                Some(GameEvent::DualCut { .. } | GameEvent::DoubleDetectorCut { .. } | GameEvent::SoloCut { .. } | GameEvent::RedRevealed { .. } | GameEvent::DetonatorTick) => {}
+                _ => return Err("A turn only ends after a cut or revealing red cables".to_string()),
+            }
+            state.current_player = next_player(state.current_player, &state.hands, &state.all_cables);
+            state.turn += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the player who acted in an event, `None` for the deal and the consequences of actions.
+pub fn acting_player(event: &GameEvent) -> Option<u32> {
+    match *event {
+        GameEvent::TokenPlaced { player, .. }
+        | GameEvent::DualCut { player, .. }
+        | GameEvent::DoubleDetectorCut { player, .. }
+        | GameEvent::SoloCut { player, .. }
+        | GameEvent::LabelPlaced { player, .. }
+        | GameEvent::Rewound { player }
+        | GameEvent::PostIt { player, .. }
+        | GameEvent::GeneralRadar { player, .. }
+        | GameEvent::RedRevealed { player } => Some(player),
+        GameEvent::Deal { .. } | GameEvent::DetonatorTick | GameEvent::TurnEnded => None,
+    }
+}
+
+/// Applies an event to the game state and appends it to the log.
+/// A new action makes the undone actions impossible to redo.
+pub fn record_event(state: &mut GameState, event: GameEvent) -> Result<(), String> {
+    apply_event(state, &event)?;
+    if is_action(&event) {
+        state.redo.clear();
+    }
+    state.log.push(event);
+    Ok(())
+}
+
+/// Whether the event is the start of an action of a player. The following events up to the next action,
+/// like the detonator tick and the end of the turn, are consequences of this action.
+pub fn is_action(event: &GameEvent) -> bool {
+    !matches!(event, GameEvent::Deal { .. } | GameEvent::TokenPlaced { .. } | GameEvent::DetonatorTick | GameEvent::TurnEnded)
+}
+
+/// Undoes the last action, including all its consequences. Only allowed in casual games.
+pub fn undo(state: &mut GameState) -> Result<(), String> {
+    if !state.meta.casual {
+        return Err("Undo is only allowed in casual games".to_string());
+    }
+    let start = state.log.iter().rposition(is_action).ok_or("There is nothing to undo")?;
+    let undone = state.log[start..].to_vec();
+    let mut rebuilt = replay(&state.log[..start])?;
+    rebuilt.redo = std::mem::take(&mut state.redo);
+    rebuilt.redo.push(undone);
+    *state = rebuilt;
+    Ok(())
+}
+
+/// Redoes the most recently undone action. Only allowed in casual games.
+pub fn redo(state: &mut GameState) -> Result<(), String> {
+    if !state.meta.casual {
+        return Err("Redo is only allowed in casual games".to_string());
+    }
+    let mut redo_stack = std::mem::take(&mut state.redo);
+    let events = redo_stack.pop().ok_or("There is nothing to redo")?;
+    for event in events {
+        record_event(state, event)?;
+    }
+    state.redo = redo_stack;
+    Ok(())
+}
+
+/// Rebuilds a game state by replaying all events of a log.
+pub fn replay(events: &[GameEvent]) -> Result<GameState, String> {
+    let (deal, rest) = events.split_first().ok_or("The game log is empty")?;
+    let mut state = state_from_deal(deal)?;
+    for (i, event) in rest.iter().enumerate() {
+        record_event(&mut state, event.clone()).map_err(|e| format!("Event {} cannot be replayed: {}", i + 1, e))?;
+    }
+    Ok(state)
+}
+
+/// Dual cut of the current player. A failed cut ticks the detonator. Ends the turn.
+/// Returns whether the cut was successful.
+pub fn play_dual_cut(state: &mut GameState, teammate: u32, position_self: u32, position_teammate: u32) -> Result<bool, String> {
+    let player = state.current_player;
+    record_event(state, GameEvent::DualCut { player, teammate, position_self, position_teammate })?;
+    let success = get_status(&state.hands[player as usize])[position_self as usize] == CableStatus::Revealed;
+    if !success {
+        record_event(state, GameEvent::DetonatorTick)?;
+    }
+    record_event(state, GameEvent::TurnEnded)?;
+    Ok(success)
+}
+
+/// Double detector cut of the current player. A failed cut ticks the detonator. Ends the turn.
+/// Returns whether the cut was successful.
+pub fn play_double_detector_cut(state: &mut GameState, teammate: u32, position_self: u32, positions_teammate: (u32, u32)) -> Result<bool, String> {
+    let player = state.current_player;
+    record_event(state, GameEvent::DoubleDetectorCut { player, teammate, position_self, positions_teammate })?;
+    let success = get_status(&state.hands[player as usize])[position_self as usize] == CableStatus::Revealed;
+    if !success {
+        record_event(state, GameEvent::DetonatorTick)?;
+    }
+    record_event(state, GameEvent::TurnEnded)?;
+    Ok(success)
+}
+
+/// Solo cut of the current player. Ends the turn.
+pub fn play_solo_cut(state: &mut GameState, value: u32) -> Result<(), String> {
+    let player = state.current_player;
+    record_event(state, GameEvent::SoloCut { player, value })?;
+    record_event(state, GameEvent::TurnEnded)
+}
+
+/// The current player reveals their remaining red cables. Ends the turn.
+pub fn play_reveal_red(state: &mut GameState) -> Result<(), String> {
+    let player = state.current_player;
+    record_event(state, GameEvent::RedRevealed { player })?;
+    record_event(state, GameEvent::TurnEnded)
+}
+
+fn check_player(state: &GameState, player: u32) -> Result<(), String> {
+    if player as usize >= state.hands.len() {
+        return Err(format!("There is no player {}", player));
+    }
+    Ok(())
+}
+
+/// Checks that it is the player's turn, for the actions which end it.
+fn check_turn(state: &GameState, player: u32) -> Result<(), String> {
+    check_player(state, player)?;
+    if player != state.current_player {
+        return Err(format!("It is player {}'s turn, not player {}'s", state.current_player, player));
+    }
+    Ok(())
+}
+
+/// Checks that the game has not started yet and the player has not placed their info token.
+fn check_token(state: &GameState, player: u32) -> Result<(), String> {
+    for event in &state.log {
+        match event {
+            GameEvent::Deal { .. } => {}
+            GameEvent::TokenPlaced { player: other, .. } if *other == player => {
+                return Err(format!("Player {} has already placed their info token", player));
+            }
+            GameEvent::TokenPlaced { .. } => {}
+            _ => return Err("Info tokens are placed before the first turn".to_string()),
+        }
+    }
+    Ok(())
+}
+
+/// Whether the event is a dual cut whose own cable is still uncut, i.e. which failed.
+fn is_failed_cut(state: &GameState, event: &GameEvent) -> bool {
+    match *event {
+        GameEvent::DualCut { player, position_self, .. } | GameEvent::DoubleDetectorCut { player, position_self, .. } => {
+            get_status(&state.hands[player as usize])[position_self as usize] != CableStatus::Revealed
+        }
+        _ => false,
+    }
+}
+
+fn check_teammate(state: &GameState, player: u32, teammate: u32) -> Result<(), String> {
+    check_player(state, player)?;
+    check_player(state, teammate)?;
+    if player == teammate {
+        return Err("A dual cut needs a teammate".to_string());
+    }
+    Ok(())
+}
+
+/// Checks that the player has a cable at the position which has not been cut yet.
+fn check_uncut(state: &GameState, player: u32, position: u32) -> Result<(), String> {
+    check_player(state, player)?;
+    let hand: &Hand = &state.hands[player as usize];
+    if position as usize >= get_cables(hand).len() {
+        return Err(format!("Player {} has no cable at position {}", player, position));
+    }
+    if get_status(hand)[position as usize] == CableStatus::Revealed {
+        return Err(format!("The cable of player {} at position {} is already cut", player, position));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::new_game;
+
+    /// Finds a dual cut for the current player which succeeds or fails, as requested.
+    fn find_dual_cut(state: &GameState, success: bool) -> (u32, u32, u32) {
+        let player = state.current_player;
+        let uncut = |hand: &Hand| -> Vec<(usize, u32)> {
+            get_cables(hand).iter().zip(get_status(hand)).enumerate()
+                .filter(|(_, (_, status))| **status != CableStatus::Revealed)
+                .map(|(i, (id, _))| (i, *id))
+                .collect()
+        };
+        let own = uncut(&state.hands[player as usize]);
+        for teammate in 0..state.hands.len() as u32 {
+            if teammate == player {
+                continue;
+            }
+            let other = uncut(&state.hands[teammate as usize]);
+            for (i, own_id) in &own {
+                for (j, other_id) in &other {
+                    let same = state.all_cables[own_id] == state.all_cables[other_id];
+                    if same == success && state.all_cables[other_id].is_multiple_of(10) {
+                        return (teammate, *i as u32, *j as u32);
+                    }
+                }
+            }
+        }
+        panic!("No matching dual cut found");
+    }
+
+    #[test]
+    fn test_replay_rebuilds_state() {
+        let mut state = new_game(GameMeta::new(12, 2, 1, 3, 2, 3), None, 11);
+        let (teammate, position_self, position_teammate) = find_dual_cut(&state, true);
+        assert_eq!(play_dual_cut(&mut state, teammate, position_self, position_teammate), Ok(true), "Expected the dual cut to succeed");
+        let (teammate, position_self, position_teammate) = find_dual_cut(&state, false);
+        assert_eq!(play_dual_cut(&mut state, teammate, position_self, position_teammate), Ok(false), "Expected the dual cut to fail");
+        assert!(state.log.contains(&GameEvent::DetonatorTick), "Expected the failed cut to tick the detonator");
+
+        let replayed = replay(&state.log).expect("Log should be replayed");
+        for (hand, replayed_hand) in state.hands.iter().zip(&replayed.hands) {
+            assert_eq!(get_cables(hand), get_cables(replayed_hand), "Expected the same hands after replay");
+            assert_eq!(get_status(hand), get_status(replayed_hand), "Expected the same cable status after replay");
+        }
+        assert_eq!((replayed.death_counter, replayed.turn, replayed.current_player), (state.death_counter, state.turn, state.current_player), "Expected the same progress after replay");
+        assert_eq!(replayed.rng, state.rng, "Expected the same RNG state after replay");
+        assert_eq!(replayed.log, state.log, "Expected the same log after replay");
+    }
+
+    #[test]
+    fn test_rejected_event_is_not_recorded() {
+        let mut state = new_game(GameMeta::new(8, 0, 0, 0, 0, 2), None, 5);
+        let log_length = state.log.len();
+        assert!(record_event(&mut state, GameEvent::DualCut { player: 0, teammate: 0, position_self: 0, position_teammate: 1 }).is_err(), "Expected a dual cut with oneself to be rejected");
+        assert!(record_event(&mut state, GameEvent::DualCut { player: 0, teammate: 1, position_self: 99, position_teammate: 0 }).is_err(), "Expected an invalid position to be rejected");
+        assert!(record_event(&mut state, GameEvent::DualCut { player: 1, teammate: 0, position_self: 0, position_teammate: 0 }).is_err(), "Expected a dual cut out of turn to be rejected");
+        assert!(record_event(&mut state, GameEvent::RedRevealed { player: 1 }).is_err(), "Expected revealing red out of turn to be rejected");
+        assert_eq!(state.log.len(), log_length, "Expected rejected events not to be recorded");
+    }
+
+    #[test]
+    fn test_bookkeeping_events_are_checked() {
+        let mut state = new_game(GameMeta::new(8, 0, 0, 0, 0, 2), None, 5);
+        assert!(record_event(&mut state, GameEvent::TokenPlaced { player: 0, position: 0 }).is_err(), "Expected a second info token to be rejected");
+        assert!(record_event(&mut state, GameEvent::DetonatorTick).is_err(), "Expected a tick without a failed cut to be rejected");
+        assert!(record_event(&mut state, GameEvent::TurnEnded).is_err(), "Expected a turn to end only after a cut");
+
+        let (teammate, position_self, position_teammate) = find_dual_cut(&state, false);
+        record_event(&mut state, GameEvent::DualCut { player: 0, teammate, position_self, position_teammate }).unwrap();
+        assert!(record_event(&mut state, GameEvent::TurnEnded).is_err(), "Expected the detonator to tick before the turn ends");
+        record_event(&mut state, GameEvent::DetonatorTick).unwrap();
+        record_event(&mut state, GameEvent::TurnEnded).unwrap();
+        let position = get_status(&state.hands[1]).iter().position(|s| *s == CableStatus::Hidden).unwrap() as u32;
+        assert!(record_event(&mut state, GameEvent::TokenPlaced { player: 1, position }).is_err(), "Expected an info token after the first turn to be rejected");
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut meta = GameMeta::new(12, 2, 1, 3, 2, 3);
+        meta.casual = true;
+        let mut state = new_game(meta, None, 13);
+        assert!(undo(&mut state).is_err(), "Expected the deal and initial clues not to be undoable");
+
+        let (teammate, position_self, position_teammate) = find_dual_cut(&state, false);
+        let before = state.log.clone();
+        let status_before = get_status(&state.hands[teammate as usize])[position_teammate as usize];
+        play_dual_cut(&mut state, teammate, position_self, position_teammate).unwrap();
+        let after = state.log.clone();
+        let death_counter = state.death_counter;
+
+        undo(&mut state).expect("Dual cut should be undone");
+        assert_eq!(state.log, before, "Expected the log to be rolled back");
+        assert_eq!(state.death_counter, death_counter + 1, "Expected the detonator to be rolled back");
+        assert_eq!(get_status(&state.hands[teammate as usize])[position_teammate as usize], status_before, "Expected the cable status to be rolled back");
+
+        redo(&mut state).expect("Dual cut should be redone");
+        assert_eq!(state.log, after, "Expected the log to be restored by redo");
+        assert_eq!(state.death_counter, death_counter, "Expected the detonator to be restored by redo");
+        assert!(redo(&mut state).is_err(), "Expected nothing left to redo");
+
+        undo(&mut state).unwrap();
+        let (teammate, position_self, position_teammate) = find_dual_cut(&state, true);
+        play_dual_cut(&mut state, teammate, position_self, position_teammate).unwrap();
+        assert!(redo(&mut state).is_err(), "Expected a new action to clear the undone actions");
+    }
+
+    #[test]
+    fn test_undo_needs_casual_game() {
+        let mut state = new_game(GameMeta::new(12, 2, 1, 3, 2, 3), None, 13);
+        let (teammate, position_self, position_teammate) = find_dual_cut(&state, true);
+        play_dual_cut(&mut state, teammate, position_self, position_teammate).unwrap();
+        assert!(undo(&mut state).is_err(), "Expected undo to be rejected in a competitive game");
+    }
+
+    #[test]
+    fn test_replay_needs_deal() {
+        assert!(replay(&[]).is_err(), "Expected an empty log to be rejected");
+        assert!(replay(&[GameEvent::TurnEnded]).is_err(), "Expected a log without deal to be rejected");
+    }
+}
@@ -1,3 +1,16 @@
+use std::collections::HashMap;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::character::{Character, init_characters};
+use crate::elements::{ALL_EQUIPMENT, Equipment, get_color, init_all_cables, init_cables_in_game, init_death_counter};
+use crate::events::{GameEvent, state_from_deal};
+use crate::mission::{Mission, get_detonator_start};
+use crate::player::{LabelKind, CableStatus, Hand, get_cables, get_status, init_cable_distribution, sort_cable_distribution};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct GameMeta {
     pub blue_max: u32,
     pub red_show: u32,
@@ -5,9 +18,22 @@ pub struct GameMeta {
     pub yellow_show: u32,
     pub yellow_keep: u32,
     pub players: u32,
+    /// Casual games allow undoing and redoing actions.
+    #[serde(default)]
+    pub casual: bool,
 }
 
+pub const MIN_PLAYERS: u32 = 2;
+pub const MAX_PLAYERS: u32 = 5;
+pub const MAX_BLUE: u32 = 12;
+/// The box holds red and yellow cables from 1 to 11.
+pub const MAX_RED: u32 = 11;
+pub const MAX_YELLOW: u32 = 11;
+/// Fewest cables a player has to be dealt.
+pub const MIN_RACK: u32 = 2;
+
 impl GameMeta {
+    /// Creates a configuration without checking it. Use `try_new` for configurations from players or files.
     pub fn new(blue_max: u32, red_show: u32, red_keep: u32, yellow_show: u32, yellow_keep: u32, players: u32) -> Self {
         GameMeta {
             blue_max,
@@ -16,17 +42,274 @@ impl GameMeta {
             yellow_show,
             yellow_keep,
             players,
+            casual: false,
         }
     }
 
-    pub fn init_game_meta() -> Self {
-        let blue_max = 12;
-        let red_show = 2;
-        let red_keep = 1;
-        let yellow_show = 3;
-        let yellow_keep = 2;
-        let players = 3;
+    /// Creates a configuration and checks that a game can be dealt with it.
+    pub fn try_new(blue_max: u32, red_show: u32, red_keep: u32, yellow_show: u32, yellow_keep: u32, players: u32) -> Result<Self, String> {
+        let meta = GameMeta::new(blue_max, red_show, red_keep, yellow_show, yellow_keep, players);
+        validate_meta(&meta)?;
+        Ok(meta)
+    }
+
+    /// Recommended configuration for a number of players. Every player gets between 10 and 17 cables.
+    pub fn recommended(players: u32) -> Result<Self, String> {
+        match players {
+            2 => GameMeta::try_new(8, 1, 1, 2, 1, players),
+            3 => GameMeta::try_new(12, 2, 1, 3, 2, players),
+            4 => GameMeta::try_new(12, 2, 1, 3, 3, players),
+            5 => GameMeta::try_new(12, 2, 1, 3, 1, players),
+            _ => Err(format!("A game needs {} to {} players, not {}", MIN_PLAYERS, MAX_PLAYERS, players)),
+        }
+    }
+}
+
+/// Checks that a game can be dealt with a configuration. Red cables lie between two blue values,
+/// yellow cables next to one, and every player has to get the same number of cables, at least `MIN_RACK`.
+pub fn validate_meta(meta: &GameMeta) -> Result<(), String> {
+    if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&meta.players) {
+        return Err(format!("A game needs {} to {} players, not {}", MIN_PLAYERS, MAX_PLAYERS, meta.players));
+    }
+    if !(1..=MAX_BLUE).contains(&meta.blue_max) {
+        return Err(format!("The highest blue value has to be between 1 and {}, not {}", MAX_BLUE, meta.blue_max));
+    }
+    if meta.red_show > MAX_RED.min(meta.blue_max - 1) {
+        return Err(format!("With blue cables up to {} at most {} red cables can be shown", meta.blue_max, MAX_RED.min(meta.blue_max - 1)));
+    }
+    if meta.yellow_show > MAX_YELLOW.min(meta.blue_max) {
+        return Err(format!("With blue cables up to {} at most {} yellow cables can be shown", meta.blue_max, MAX_YELLOW.min(meta.blue_max)));
+    }
+    if meta.red_keep > meta.red_show {
+        return Err(format!("Cannot keep {} of {} red cables", meta.red_keep, meta.red_show));
+    }
+    if meta.yellow_keep > meta.yellow_show {
+        return Err(format!("Cannot keep {} of {} yellow cables", meta.yellow_keep, meta.yellow_show));
+    }
+    let cables = 4 * meta.blue_max + meta.red_keep + meta.yellow_keep;
+    if cables < MIN_RACK * meta.players {
+        return Err(format!("{} cables are too few for {} players, every player needs at least {}", cables, meta.players, MIN_RACK));
+    }
+    if !cables.is_multiple_of(meta.players) {
+        return Err(format!("{} cables cannot be split evenly between {} players", cables, meta.players));
+    }
+    Ok(())
+}
+
+/// Everything that changes during a game.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameState {
+    pub meta: GameMeta,
+    pub mission: Option<Mission>,
+    /// Seed the game was set up with. The same seed and configuration always deal the same game.
+    pub seed: u64,
+    /// Source of all randomness in the game, saved with the game so that a loaded game continues identically.
+    pub rng: ChaCha8Rng,
+    pub all_cables: HashMap<u32, u32>,
+    pub hands: Vec<Hand>,
+    pub characters: Vec<Character>,
+    pub death_counter: u32,
+    pub current_player: u32,
+    pub turn: u32,
+    /// All events of the game, starting with the deal.
+    #[serde(default)]
+    pub log: Vec<GameEvent>,
+    /// Undone actions, the most recently undone action last.
+    #[serde(skip)]
+    pub redo: Vec<Vec<GameEvent>>,
+}
+
+impl GameState {
+    /// Deals a new game without info tokens. The players place their info tokens before the first turn.
+    pub fn deal(meta: GameMeta, mission: Option<Mission>, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let death_counter = match &mission {
+            Some(mission) => get_detonator_start(mission, meta.players),
+            None => init_death_counter(meta.players),
+        };
+        let all_cables = init_all_cables(meta.blue_max, meta.red_show, meta.yellow_show);
+        let in_game_cables = init_cables_in_game(&all_cables, meta.red_keep, meta.yellow_keep, &mut rng);
+        let mut cable_distribution = init_cable_distribution(&in_game_cables, meta.players, &mut rng);
+        sort_cable_distribution(&mut cable_distribution, &all_cables);
+        let characters = init_characters(meta.players, &mut rng);
+
+        let deal = GameEvent::Deal {
+            meta,
+            mission,
+            seed,
+            rng,
+            all_cables,
+            hands: cable_distribution,
+            characters,
+            death_counter,
+        };
+        state_from_deal(&deal).expect("Deal event is valid")
+    }
+
+}
+
+/// Returns the equipment which can still be used. Without a mission, all equipment is available once.
+pub fn available_equipment(state: &GameState) -> Vec<Equipment> {
+    let mut equipment = match &state.mission {
+        Some(mission) => mission.equipment.clone(),
+        None => ALL_EQUIPMENT.to_vec(),
+    };
+    for event in &state.log {
+        if let GameEvent::LabelPlaced { kind, .. } = event
+            && let Some(index) = equipment.iter().position(|e| *e == label_equipment(*kind))
+        {
+            equipment.remove(index);
+        }
+    }
+    equipment
+}
+
+/// Returns the label of an equipment card.
+pub fn equipment_label(equipment: Equipment) -> LabelKind {
+    match equipment {
+        Equipment::LabelEqual => LabelKind::Equal,
+        Equipment::LabelDifferent => LabelKind::Different,
+    }
+}
 
-        GameMeta::new(blue_max, red_show, red_keep, yellow_show, yellow_keep, players)
+/// Returns the equipment card of a label.
+pub fn label_equipment(kind: LabelKind) -> Equipment {
+    match kind {
+        LabelKind::Equal => Equipment::LabelEqual,
+        LabelKind::Different => Equipment::LabelDifferent,
     }
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Debug, PartialEq, Copy)]
+pub enum GameOutcome {
+    /// All blue and yellow cables are cut.
+    Defused,
+    /// A red cable was cut.
+    RedCableCut,
+    /// The detonator reached zero.
+    DetonatorExpired,
+}
+
+/// Returns the outcome of the game, or `None` while the game is still running.
+pub fn get_outcome(hands: &Vec<Hand>, all_cables: &HashMap<u32, u32>, death_counter: u32) -> Option<GameOutcome> {
+    let mut all_cut = true;
+    for hand in hands {
+        for (id, status) in get_cables(hand).iter().zip(get_status(hand)) {
+            let is_red = get_color(*id, all_cables) == "red";
+            if is_red && *status == CableStatus::Revealed {
+                return Some(GameOutcome::RedCableCut);
+            }
+            if !is_red && *status != CableStatus::Revealed {
+                all_cut = false;
+            }
+        }
+    }
+    if death_counter == 0 {
+        return Some(GameOutcome::DetonatorExpired);
+    }
+    if all_cut {
+        return Some(GameOutcome::Defused);
+    }
+    None
+}
+
+/// Returns the next player after `player` who still has something to do: a hidden cable,
+/// or a clue which is not red. Red cables which are face up are never cut.
+pub fn next_player(player: u32, hands: &[Hand], all_cables: &HashMap<u32, u32>) -> u32 {
+    let players = hands.len() as u32;
+    for step in 1..=players {
+        let candidate = (player + step) % players;
+        let hand = &hands[candidate as usize];
+        let active = get_cables(hand).iter().zip(get_status(hand)).any(|(id, status)| match status {
+            CableStatus::Hidden => true,
+            CableStatus::Clue => get_color(*id, all_cables) != "red",
+            CableStatus::Revealed => false,
+        });
+        if active {
+            return candidate;
+        }
+    }
+    player
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::new_game;
+    use crate::player::{change_cable_status, init_hands};
+
+    #[test]
+    fn test_new_game_state() {
+        let state = new_game(GameMeta::new(8, 2, 1, 2, 2, 3), None, 7);
+        let dealt: usize = state.hands.iter().map(|hand| get_cables(hand).len()).sum();
+        assert_eq!(dealt, 4 * 8 + 1 + 2, "Expected all cables in game to be dealt, found {}", dealt);
+        assert_eq!(state.characters.len(), 3, "Expected one character per player");
+        assert_eq!((state.death_counter, state.current_player, state.turn), (4, 0, 1), "Expected a fresh game state");
+    }
+
+    #[test]
+    fn test_same_seed_same_game() {
+        let first = new_game(GameMeta::new(12, 2, 1, 3, 2, 4), None, 42);
+        let second = new_game(GameMeta::new(12, 2, 1, 3, 2, 4), None, 42);
+        for (hand_first, hand_second) in first.hands.iter().zip(&second.hands) {
+            let values_first: Vec<u32> = get_cables(hand_first).iter().map(|id| first.all_cables[id]).collect();
+            let values_second: Vec<u32> = get_cables(hand_second).iter().map(|id| second.all_cables[id]).collect();
+            assert_eq!(values_first, values_second, "Expected the same seed to deal the same hands");
+            assert_eq!(get_status(hand_first), get_status(hand_second), "Expected the same seed to place the same clues");
+        }
+    }
+
+    #[test]
+    fn test_get_outcome() {
+        let all_cables = HashMap::from([(1, 10), (2, 10), (3, 15), (4, 21)]);
+        let mut hands = init_hands(vec![vec![1, 3], vec![2, 4]]);
+        assert_eq!(get_outcome(&hands, &all_cables, 2), None, "Expected the game to be running");
+        assert_eq!(get_outcome(&hands, &all_cables, 0), Some(GameOutcome::DetonatorExpired), "Expected the detonator to expire");
+
+        for id in [1, 2, 4] {
+            change_cable_status(id, &mut hands, CableStatus::Revealed);
+        }
+        assert_eq!(get_outcome(&hands, &all_cables, 2), Some(GameOutcome::Defused), "Expected the bomb to be defused with only red cables left");
+
+        change_cable_status(3, &mut hands, CableStatus::Revealed);
+        assert_eq!(get_outcome(&hands, &all_cables, 2), Some(GameOutcome::RedCableCut), "Expected a cut red cable to explode the bomb");
+    }
+
+    #[test]
+    fn test_available_equipment() {
+        let mut state = new_game(GameMeta::new(8, 0, 0, 0, 0, 2), None, 1);
+        assert_eq!(available_equipment(&state), ALL_EQUIPMENT.to_vec(), "Expected all equipment without a mission");
+        state.log.push(GameEvent::LabelPlaced { player: 0, position: 0, kind: LabelKind::Equal });
+        assert_eq!(available_equipment(&state), vec![Equipment::LabelDifferent], "Expected the used label to be gone");
+    }
+
+    #[test]
+    fn test_validate_meta() {
+        assert!(GameMeta::try_new(12, 2, 1, 3, 2, 3).is_ok(), "Expected the default configuration to be valid");
+        assert!(GameMeta::try_new(12, 1, 2, 3, 2, 3).is_err(), "Expected keeping more red cables than shown to be rejected");
+        assert!(GameMeta::try_new(12, 2, 1, 2, 3, 3).is_err(), "Expected keeping more yellow cables than shown to be rejected");
+        assert!(GameMeta::try_new(4, 4, 1, 0, 0, 3).is_err(), "Expected red cables above the highest blue value to be rejected");
+        assert!(GameMeta::try_new(13, 0, 0, 0, 0, 3).is_err(), "Expected too many blue values to be rejected");
+        assert!(GameMeta::try_new(12, 2, 1, 3, 2, 6).is_err(), "Expected too many players to be rejected");
+        assert!(GameMeta::try_new(2, 0, 0, 0, 0, 5).is_err(), "Expected too few cables per player to be rejected");
+        assert!(GameMeta::try_new(8, 1, 1, 2, 2, 2).is_err(), "Expected 35 cables for 2 players to be rejected as an uneven split");
+        for players in MIN_PLAYERS..=MAX_PLAYERS {
+            let meta = GameMeta::recommended(players).unwrap_or_else(|e| panic!("Expected a recommended configuration for {} players: {}", players, e));
+            assert_eq!(meta.players, players, "Expected the recommended configuration to be for {} players", players);
+            assert_eq!(validate_meta(&meta), Ok(()), "Expected the recommended configuration for {} players to be valid", players);
+        }
+        assert!(GameMeta::recommended(1).is_err(), "Expected no recommendation for a single player");
+    }
+
+    #[test]
+    fn test_next_player() {
+        let all_cables = HashMap::from([(1, 10), (2, 20), (3, 15)]);
+        let mut hands = init_hands(vec![vec![1], vec![2], vec![3]]);
+        assert_eq!(next_player(0, &hands, &all_cables), 1, "Expected player 1 to follow player 0");
+        assert_eq!(next_player(2, &hands, &all_cables), 0, "Expected player 0 to follow player 2");
+        change_cable_status(2, &mut hands, CableStatus::Revealed);
+        assert_eq!(next_player(0, &hands, &all_cables), 2, "Expected player 1 to be skipped without cables left");
+        change_cable_status(3, &mut hands, CableStatus::Clue);
+        assert_eq!(next_player(0, &hands, &all_cables), 0, "Expected player 2 to be skipped with a face up red cable");
+    }
+}
@@ -1,28 +1,92 @@
-
-
 mod game;
+mod character;
+mod command;
+mod compact;
 mod elements;
+mod events;
+mod mission;
+mod montecarlo;
+mod notation;
+mod options;
 mod player;
+mod save;
+mod seat;
+mod simulation;
 mod actions;
+mod advice;
+mod bot;
+mod campaign;
+mod deduction;
+mod difficulty;
 mod terminal;
+#[cfg(test)]
+mod testing;
+mod view;
+
+use std::env;
 
 use crate::terminal::*;
-use crate::actions::*;
 
 fn main() {
+    if env::args().any(|arg| arg == "--list-missions") {
+        show_mission_catalogue();
+        return;
+    }
+    let args: Vec<String> = env::args().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--replay") {
+        match args.get(index + 1) {
+            Some(path) => replay_game_record(path),
+            None => println!("Please give the file of a game record after --replay."),
+        }
+        return;
+    }
+    if let Some(index) = args.iter().position(|arg| arg == "--simulate") {
+        run_simulation_command(&args[index + 1..]);
+        return;
+    }
+    let options = match options::parse_game_options(&args[1..]) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
     println!("This is Bomb Buster!");
-    let (blue_max, red_show, red_keep, yellow_show, yellow_keep, players) = (12, 2, 1, 3, 2, 3);
-    let game = game::GameMeta::new(blue_max, red_show, red_keep, yellow_show, yellow_keep, players);
-    let death_counter = elements::init_death_counter(players);
-    println!("Death counter: {}", death_counter);
-    let all_cables = elements::init_all_cables(blue_max, red_show, yellow_show);
-    let in_game_cables = elements::init_cables_in_game(&all_cables, red_keep, yellow_keep);
-    let mut cable_distribution = player::init_cable_distribution(&in_game_cables, players);
-    player::sort_cable_distribution(&mut cable_distribution, &all_cables);
-    let mut hands = player::init_hands(cable_distribution);
-    let current_player = 0; // Assuming player 0 is the current player
-    hint_random(&mut hands);
-    show_cable_info(&all_cables, &game);
-    show_hands(current_player, &hands, &all_cables);
-    create_new_game();
+    let (game, mission, seats) = match create_new_game(&options) {
+        Ok(game) => game,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let mut state = game::GameState::deal(game, mission, seed);
+    let hot_seat = options.hot_seat.unwrap_or_else(|| def_hot_seat(&seats));
+    let mut players = seat::init_players(&seats, seed, options::options_budget(&options), hot_seat);
+    println!("Death counter: {}", state.death_counter);
+    if let Some(mission) = &state.mission {
+        show_mission(mission);
+    }
+    show_cable_info(&view::public_view(&state));
+    show_characters(&state.characters);
+    if let Err(e) = seat::place_tokens(&mut state, &mut players) {
+        println!("{}", e);
+        return;
+    }
+
+    let outcome = match play_game(&mut state, &mut players) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    show_outcome(outcome, state.death_counter);
+    if outcome != game::GameOutcome::Defused {
+        show_log(&state.log);
+    }
+    if let Some(mission) = state.mission.as_ref().filter(|m| mission::get_catalogue_mission(m.number).as_ref() == Some(*m)) {
+        record_campaign(mission.number, outcome, state.death_counter);
+    }
+    export_game_record(&state, outcome);
 }
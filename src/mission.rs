@@ -0,0 +1,165 @@
+//! This module contains the missions. A mission describes the whole setup of a game: which cables are used,
+//! where the detonator starts, which equipment is allowed and which special rules apply.
+//! Missions are stored as JSON files, cable values follow the same representation as in `elements`.
+//! The built-in missions in the `missions` directory are embedded into the binary as the mission catalogue.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::elements::{Equipment, init_death_counter};
+use crate::game::{GameMeta, MAX_PLAYERS, MIN_PLAYERS};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Mission {
+    pub number: u32,
+    pub name: String,
+    /// Short story text shown before the mission starts.
+    #[serde(default)]
+    pub intro: String,
+    pub blue_max: u32,
+    pub red_show: u32,
+    pub red_keep: u32,
+    pub yellow_show: u32,
+    pub yellow_keep: u32,
+    /// Starting value of the detonator. If not set, it depends on the number of players.
+    #[serde(default)]
+    pub detonator_start: Option<u32>,
+    #[serde(default)]
+    pub equipment: Vec<Equipment>,
+    /// Rules of the mission that differ from the standard rules, shown to the players before the game.
+    #[serde(default)]
+    pub special_rules: Vec<String>,
+}
+
+/// The built-in missions, ordered by mission number.
+const CATALOGUE: [&str; 8] = [
+    include_str!("../missions/01_first_day_on_the_job.json"),
+    include_str!("../missions/02_yellow_alert.json"),
+    include_str!("../missions/03_seeing_red.json"),
+    include_str!("../missions/04_full_spectrum.json"),
+    include_str!("../missions/05_doubt.json"),
+    include_str!("../missions/06_hidden_threat.json"),
+    include_str!("../missions/07_short_fuse.json"),
+    include_str!("../missions/08_double_trouble.json"),
+];
+
+/// Returns all built-in missions.
+pub fn mission_catalogue() -> Vec<Mission> {
+    CATALOGUE
+        .iter()
+        .map(|data| parse_mission(data).expect("Built-in mission is invalid"))
+        .collect()
+}
+
+/// Returns the built-in mission with the given number, if there is one.
+pub fn get_catalogue_mission(number: u32) -> Option<Mission> {
+    mission_catalogue().into_iter().find(|mission| mission.number == number)
+}
+
+/// Parses a mission from its JSON representation.
+pub fn parse_mission(data: &str) -> Result<Mission, String> {
+    let mission: Mission = serde_json::from_str(data).map_err(|e| format!("Invalid mission definition: {}", e))?;
+    if mission.detonator_start == Some(0) {
+        return Err(format!("Mission {} starts with the detonator at zero", mission.number));
+    }
+    if mission_players(&mission).is_empty() {
+        mission_game_meta(&mission, MIN_PLAYERS)?;
+        return Err(format!("Mission {} cannot be played with {} to {} players", mission.number, MIN_PLAYERS, MAX_PLAYERS));
+    }
+    Ok(mission)
+}
+
+/// Loads a mission from a JSON file.
+pub fn load_mission(path: &Path) -> Result<Mission, String> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read mission file {}: {}", path.display(), e))?;
+    parse_mission(&data)
+}
+
+/// Creates the game configuration of a mission for the given number of players.
+pub fn mission_game_meta(mission: &Mission, players: u32) -> Result<GameMeta, String> {
+    GameMeta::try_new(
+        mission.blue_max,
+        mission.red_show,
+        mission.red_keep,
+        mission.yellow_show,
+        mission.yellow_keep,
+        players,
+    )
+    .map_err(|e| format!("Mission {} cannot be played with {} players: {}", mission.number, players, e))
+}
+
+/// Returns the numbers of players a mission can be played with. Its cables have to be split evenly between them.
+pub fn mission_players(mission: &Mission) -> Vec<u32> {
+    (MIN_PLAYERS..=MAX_PLAYERS).filter(|players| mission_game_meta(mission, *players).is_ok()).collect()
+}
+
+/// Returns the starting value of the detonator for a mission.
+pub fn get_detonator_start(mission: &Mission, players: u32) -> u32 {
+    mission.detonator_start.unwrap_or(init_death_counter(players))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mission() {
+        let data = r#"{
+            "number": 8,
+            "name": "Test mission",
+            "blue_max": 12,
+            "red_show": 2,
+            "red_keep": 1,
+            "yellow_show": 3,
+            "yellow_keep": 2,
+            "detonator_start": 5,
+            "equipment": ["LabelEqual", "LabelDifferent"],
+            "special_rules": ["No talking"]
+        }"#;
+        let mission = parse_mission(data).expect("Mission should be parsed");
+        assert_eq!(mission.number, 8, "Expected mission number 8, found {}", mission.number);
+        assert_eq!(mission.equipment, vec![Equipment::LabelEqual, Equipment::LabelDifferent], "Expected both labels to be allowed");
+        assert_eq!(get_detonator_start(&mission, 3), 5, "Expected the detonator start of the mission");
+
+        let game_meta = mission_game_meta(&mission, 3).unwrap();
+        assert_eq!((game_meta.blue_max, game_meta.red_keep, game_meta.yellow_keep), (12, 1, 2), "Game meta should follow the mission");
+    }
+
+    #[test]
+    fn test_parse_mission_defaults() {
+        let data = r#"{"number": 1, "name": "Minimal", "blue_max": 6, "red_show": 0, "red_keep": 0, "yellow_show": 0, "yellow_keep": 0}"#;
+        let mission = parse_mission(data).expect("Mission should be parsed");
+        assert!(mission.equipment.is_empty(), "Expected no equipment by default");
+        assert!(mission.special_rules.is_empty(), "Expected no special rules by default");
+        assert_eq!(get_detonator_start(&mission, 4), init_death_counter(4), "Expected the default detonator start");
+    }
+
+    #[test]
+    fn test_mission_catalogue() {
+        let catalogue = mission_catalogue();
+        assert_eq!(catalogue.len(), CATALOGUE.len(), "Expected every built-in mission to be parsed");
+        for (i, mission) in catalogue.iter().enumerate() {
+            assert_eq!(mission.number, i as u32 + 1, "Expected missions to be numbered in order");
+            assert!(!mission.intro.is_empty(), "Expected mission {} to have an intro text", mission.number);
+            assert!(mission.red_keep <= mission.red_show, "Mission {} keeps more red cables than it shows", mission.number);
+            assert!(mission.yellow_keep <= mission.yellow_show, "Mission {} keeps more yellow cables than it shows", mission.number);
+            assert!(!mission_players(mission).is_empty(), "Expected mission {} to be playable with some number of players", mission.number);
+        }
+        assert_eq!(get_catalogue_mission(3).map(|m| m.name), Some("Seeing Red".to_string()), "Expected to find mission 3 by number");
+        assert_eq!(get_catalogue_mission(1).map(|m| mission_players(&m)), Some(vec![2, 3, 4]), "Expected the 24 cables of mission 1 to be split between 2, 3 or 4 players");
+        assert!(get_catalogue_mission(99).is_none(), "Expected no mission 99");
+    }
+
+    #[test]
+    fn test_parse_invalid_mission() {
+        assert!(parse_mission(r#"{"number": 1}"#).is_err(), "Expected incomplete mission to be rejected");
+        let data = r#"{"number": 1, "name": "Broken", "blue_max": 6, "red_show": 1, "red_keep": 2, "yellow_show": 0, "yellow_keep": 0}"#;
+        assert!(parse_mission(data).is_err(), "Expected a mission keeping more red cables than it shows to be rejected");
+        let data = r#"{"number": 1, "name": "Broken", "blue_max": 6, "red_show": 0, "red_keep": 0, "yellow_show": 0, "yellow_keep": 0, "detonator_start": 0}"#;
+        assert!(parse_mission(data).is_err(), "Expected a mission starting with the detonator at zero to be rejected");
+        assert!(load_mission(Path::new("does/not/exist.json")).is_err(), "Expected missing file to be rejected");
+    }
+}
@@ -0,0 +1,289 @@
+//! This module contains the Monte Carlo bot. It samples deals which are consistent with its `PlayerView`,
+//! plays each candidate move in every sampled deal and finishes the game with quick playouts on a `CompactGame`.
+//! The move which defuses the bomb most often is chosen. The search stops after a number of iterations or a time limit.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use crate::advice::{cut_advice, sample_deal};
+use crate::bot::{fallback_move, only_red_left, solo_cut_value, token_position};
+use crate::compact::{CompactGame, VALUE_CODES, code_at, code_copies, compact_dual_cut, compact_game, compact_outcome, compact_reveal_red, compact_solo_cut, is_red_code, uncut_mask, value_code};
+use crate::deduction::possible_values;
+use crate::events::{GameEvent, state_from_deal};
+use crate::game::{GameOutcome, GameState};
+use crate::player::{CableStatus, add_announced_cable, add_label, change_cable_status, get_cables};
+use crate::seat::{Move, Player, Turn};
+use crate::view::PlayerView;
+
+/// Number of sampled deals behind the candidate moves.
+const CANDIDATE_SAMPLES: usize = 100;
+/// Number of dual cuts which are evaluated by playouts.
+const CANDIDATES: usize = 5;
+
+/// How long the Monte Carlo bot searches for a move. Each iteration samples one deal and plays out every candidate move in it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Budget {
+    pub iterations: usize,
+    /// Stops the search early once this time has passed.
+    pub time: Option<Duration>,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Budget { iterations: 100, time: Some(Duration::from_secs(2)) }
+    }
+}
+
+pub struct MonteCarloBot {
+    rng: ChaCha8Rng,
+    budget: Budget,
+}
+
+impl MonteCarloBot {
+    pub fn new(seed: u64, budget: Budget) -> Self {
+        MonteCarloBot { rng: ChaCha8Rng::seed_from_u64(seed), budget }
+    }
+}
+
+impl Player for MonteCarloBot {
+    fn choose_token(&mut self, view: &PlayerView) -> u32 {
+        token_position(view)
+    }
+
+    fn choose_turn(&mut self, view: &PlayerView) -> Turn {
+        if only_red_left(view) {
+            return Turn::Play(Move::RevealRed);
+        }
+        if let Some(value) = solo_cut_value(view) {
+            return Turn::Play(Move::SoloCut { value });
+        }
+        let candidates = candidate_moves(view, &mut self.rng);
+        if candidates.len() <= 1 {
+            return Turn::Play(candidates.first().copied().unwrap_or_else(|| fallback_move(view)));
+        }
+
+        let possible = possible_values(view);
+        let start = Instant::now();
+        let mut scores = vec![0.0; candidates.len()];
+        for _ in 0..self.budget.iterations {
+            if self.budget.time.is_some_and(|time| start.elapsed() >= time) {
+                break;
+            }
+            let Some(deal) = sample_deal(view, &possible, &mut self.rng) else {
+                continue;
+            };
+            let Ok(game) = compact_game(&determinize(view, &deal)) else {
+                continue;
+            };
+            for (mv, score) in candidates.iter().zip(scores.iter_mut()) {
+                let mut playout = game;
+                if compact_move(&mut playout, *mv).is_ok() {
+                    *score += play_out(&mut playout);
+                }
+            }
+        }
+        let best = (0..candidates.len()).max_by(|a, b| scores[*a].total_cmp(&scores[*b])).unwrap_or(0);
+        Turn::Play(candidates[best])
+    }
+}
+
+/// The most promising dual cuts: the likeliest ones on cables which cannot be red. If every cable can be red,
+/// the likeliest ones on cables which were red in no sampled deal, or the likeliest ones at all.
+fn candidate_moves(view: &PlayerView, rng: &mut impl Rng) -> Vec<Move> {
+    let advice = cut_advice(view, CANDIDATE_SAMPLES, rng);
+    let safe: Vec<_> = advice.iter().filter(|a| !a.can_be_red).collect();
+    let unlikely: Vec<_> = advice.iter().filter(|a| a.red == 0.0).collect();
+    let chosen = match (safe.is_empty(), unlikely.is_empty()) {
+        (false, _) => safe,
+        (true, false) => unlikely,
+        (true, true) => advice.iter().collect(),
+    };
+    chosen.into_iter()
+        .take(CANDIDATES)
+        .map(|a| Move::DualCut { teammate: a.teammate, position_self: a.position_self, position_teammate: a.position })
+        .collect()
+}
+
+/// Creates a complete game state from a sampled deal. Statuses, labels and claims are taken from the view.
+pub fn determinize(view: &PlayerView, deal: &Vec<Vec<u32>>) -> GameState {
+    let mut all_cables: HashMap<u32, u32> = HashMap::new();
+    let mut hands: Vec<Vec<u32>> = Vec::new();
+    let mut next_id = 1;
+    for rack in deal {
+        let mut ids = Vec::new();
+        for value in rack {
+            all_cables.insert(next_id, *value);
+            ids.push(next_id);
+            next_id += 1;
+        }
+        hands.push(ids);
+    }
+    // Shown red and yellow cables which are not in play still belong to the game.
+    for value in view.red_values.iter().chain(&view.yellow_values) {
+        if !deal.iter().flatten().any(|v| v == value) {
+            all_cables.insert(next_id, *value);
+            next_id += 1;
+        }
+    }
+
+    let event = GameEvent::Deal {
+        meta: view.meta.clone(),
+        mission: None,
+        seed: 0,
+        rng: ChaCha8Rng::seed_from_u64(0),
+        all_cables,
+        hands,
+        characters: view.characters.clone(),
+        death_counter: view.death_counter,
+    };
+    let mut state = state_from_deal(&event).expect("Deal event is valid");
+    state.current_player = view.current_player;
+    state.turn = view.turn;
+    for (r, rack) in view.racks.iter().enumerate() {
+        let ids = get_cables(&state.hands[r]).to_vec();
+        for (i, slot) in rack.slots.iter().enumerate() {
+            if slot.status != CableStatus::Hidden {
+                change_cable_status(ids[i], &mut state.hands, slot.status);
+            }
+        }
+        for label in &rack.labels {
+            add_label(&mut state.hands[r], *label);
+        }
+        let mut announced: Vec<usize> = Vec::new();
+        for value in &rack.claims {
+            let claimed = (0..ids.len()).find(|i| !announced.contains(i) && deal[r][*i] == *value && rack.slots[*i].status != CableStatus::Revealed);
+            if let Some(i) = claimed {
+                announced.push(i);
+                add_announced_cable(&mut state.hands[r], ids[i]);
+            }
+        }
+    }
+    state
+}
+
+/// Plays a move in the compact copy of a game.
+fn compact_move(game: &mut CompactGame, mv: Move) -> Result<(), String> {
+    match mv {
+        Move::DualCut { teammate, position_self, position_teammate } => {
+            compact_dual_cut(game, teammate as usize, position_self as usize, position_teammate as usize).map(|_| ())
+        }
+        Move::SoloCut { value } => compact_solo_cut(game, value_code(value).ok_or(format!("{} is no cable value", value))?),
+        Move::RevealRed => compact_reveal_red(game),
+        Move::DoubleDetectorCut { .. } => Err("A compact game has no characters".to_string()),
+    }
+}
+
+/// Finishes the game with a quick policy for every player and scores the result:
+/// 1 for a defused bomb plus a little for every remaining detonator step, 0 for an explosion.
+fn play_out(game: &mut CompactGame) -> f64 {
+    let max_turns: u32 = game.racks.iter().map(|rack| rack.len as u32).sum::<u32>() * 2;
+    for _ in 0..max_turns {
+        match compact_outcome(game) {
+            Some(GameOutcome::Defused) => return 1.0 + game.death_counter as f64 / 100.0,
+            Some(_) => return 0.0,
+            None => {}
+        }
+        if !playout_turn(game) {
+            return 0.0;
+        }
+    }
+    0.0
+}
+
+/// A quick policy which only uses what the current player sees: a solo cut if possible, otherwise the dual cut
+/// on the teammate cable with the narrowest range between visible neighbours which includes an own value, preferring
+/// ranges without shown red values. With only red cables left they are revealed. Returns false if nothing can be played.
+fn playout_turn(game: &mut CompactGame) -> bool {
+    let player = game.current_player as usize;
+    let own = game.racks[player];
+    let own_uncut = uncut_mask(&own);
+    let mut own_codes: u64 = 0;
+    for code in (0..VALUE_CODES).filter(|code| !is_red_code(*code) && own.values[*code] & own_uncut != 0) {
+        let held = (own.values[code] & own_uncut).count_ones();
+        let cut: u32 = game.racks[..game.players as usize].iter().map(|rack| (rack.values[code] & rack.cut).count_ones()).sum();
+        if held + cut == code_copies(code) && compact_solo_cut(game, code).is_ok() {
+            return true;
+        }
+        own_codes |= 1 << code;
+    }
+    let red_shown = (0..VALUE_CODES).filter(|code| is_red_code(*code)).fold(0u64, |mask, code| mask | (1 << code)) & game.shown;
+
+    // Best target as (red risk, range width, teammate, own position, teammate position).
+    let mut best: Option<(bool, usize, usize, usize, usize)> = None;
+    for (r, rack) in game.racks[..game.players as usize].iter().enumerate() {
+        if r == player {
+            continue;
+        }
+        let visible = rack.clue | rack.cut;
+        for i in (0..rack.len as usize).filter(|i| uncut_mask(rack) & (1 << i) != 0) {
+            let (low, high) = if rack.clue & (1 << i) != 0 {
+                let code = code_at(rack, i);
+                (code, code)
+            } else {
+                let below = visible & ((1 << i) - 1);
+                let above = visible.checked_shr(i as u32 + 1).unwrap_or(0);
+                let low = if below == 0 { 0 } else { code_at(rack, 63 - below.leading_zeros() as usize) };
+                let high = if above == 0 { VALUE_CODES - 1 } else { code_at(rack, i + 1 + above.trailing_zeros() as usize) };
+                (low, high)
+            };
+            let range = ((1u64 << (high + 1)) - 1) & !((1u64 << low) - 1);
+            if own_codes & range == 0 {
+                continue;
+            }
+            let code = (own_codes & range).trailing_zeros() as usize;
+            let position_self = (own.values[code] & own_uncut).trailing_zeros() as usize;
+            let target = (red_shown & range != 0, high - low, r, position_self, i);
+            if best.is_none_or(|b| (target.0, target.1) < (b.0, b.1)) {
+                best = Some(target);
+            }
+        }
+    }
+    match best {
+        Some((_, _, teammate, position_self, position_teammate)) => compact_dual_cut(game, teammate, position_self, position_teammate).is_ok(),
+        None => compact_reveal_red(game).is_ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::new_game;
+    use crate::game::{GameMeta, get_outcome};
+    use crate::seat::{place_tokens, play_move};
+    use crate::view::player_view;
+
+    #[test]
+    fn test_determinize_keeps_view() {
+        let state = new_game(GameMeta::new(8, 2, 1, 2, 1, 3), None, 6);
+        let view = player_view(&state, 2);
+        let deal = sample_deal(&view, &possible_values(&view), &mut ChaCha8Rng::seed_from_u64(1))
+            .or_else(|| sample_deal(&view, &possible_values(&view), &mut ChaCha8Rng::seed_from_u64(2)))
+            .expect("Expected a consistent deal");
+        let sampled = determinize(&view, &deal);
+        assert_eq!(player_view(&sampled, 2).racks, view.racks, "Expected the sampled game to look the same to the player");
+        assert_eq!(sampled.death_counter, state.death_counter, "Expected the detonator to be kept");
+    }
+
+    #[test]
+    fn test_monte_carlo_bots_finish_game() {
+        let mut state = GameState::deal(GameMeta::new(6, 1, 1, 0, 0, 2), None, 3);
+        let mut players: Vec<Box<dyn Player>> = (0..2)
+            .map(|i| -> Box<dyn Player> { Box::new(MonteCarloBot::new(i, Budget { iterations: 5, time: None })) })
+            .collect();
+        place_tokens(&mut state, &mut players).unwrap();
+        for _ in 0..100 {
+            if get_outcome(&state.hands, &state.all_cables, state.death_counter).is_some() {
+                return;
+            }
+            let player = state.current_player;
+            let Turn::Play(mv) = players[player as usize].choose_turn(&player_view(&state, player)) else {
+                panic!("Expected bots to only play moves");
+            };
+            play_move(&mut state, mv).expect("Expected bots to only make valid moves");
+        }
+        panic!("Expected the game to end");
+    }
+}
@@ -0,0 +1,475 @@
+//! This module contains the notation for game records, similar to PGN for chess.
+//! A record starts with the setup as tags, followed by one action per line:
+//!
+//! ```text
+//! [Players "3"]
+//! [BlueMax "12"]
+//! [Red "1/2"]
+//! [Yellow "2/3"]
+//! [Seed "42"]
+//! [Result "Defused"]
+//!
+//! 0. P0 token #2
+//! 0. P1 token #7
+//! 0. P2 token #4
+//! 1. P0#3 dual 4 -> P2#5 fail
+//! 2. P1 label = #6
+//! 2. P1 solo 7
+//! ```
+//!
+//! Red and yellow are given as kept/shown. The info tokens placed before the first turn are written as turn 0. Players and positions start at 0, values are written like on the cables (e.g. 1.1 for a yellow cable).
+//! The deal is not part of the record: the same setup and seed always deal the same game. A built-in mission is given by its number
+//! in a `Mission` tag, any other mission is written out as JSON in a `MissionData` tag.
+
+use crate::events::*;
+use crate::game::{GameMeta, GameOutcome, GameState, get_outcome};
+use crate::mission::{Mission, get_catalogue_mission, parse_mission};
+use crate::player::{LabelKind, get_cables};
+use crate::elements::get_value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRecord {
+    pub meta: GameMeta,
+    pub mission: Option<Mission>,
+    pub seed: u64,
+    pub actions: Vec<RecordedAction>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedAction {
+    pub turn: u32,
+    pub event: GameEvent,
+    /// Value of the player's own cable used for a cut.
+    pub value: Option<u32>,
+    /// Whether a cut was successful.
+    pub success: Option<bool>,
+}
+
+/// Creates the record of a game from its log.
+pub fn record_from_state(state: &GameState) -> GameRecord {
+    let mut actions = vec![];
+    let mut turn = 1;
+    for event in &state.log {
+        match *event {
+            GameEvent::TurnEnded => turn += 1,
+            GameEvent::TokenPlaced { .. } => actions.push(RecordedAction { turn: 0, event: event.clone(), value: None, success: None }),
+            GameEvent::DualCut { player, teammate, position_self, position_teammate } => {
+                let value = cable_value(state, player, position_self);
+                let success = value == cable_value(state, teammate, position_teammate);
+                actions.push(RecordedAction { turn, event: event.clone(), value: Some(value), success: Some(success) });
+            }
+            GameEvent::DoubleDetectorCut { player, teammate, position_self, positions_teammate } => {
+                let value = cable_value(state, player, position_self);
+                let success = value == cable_value(state, teammate, positions_teammate.0) || value == cable_value(state, teammate, positions_teammate.1);
+                actions.push(RecordedAction { turn, event: event.clone(), value: Some(value), success: Some(success) });
+            }
+            _ if is_action(event) => actions.push(RecordedAction { turn, event: event.clone(), value: None, success: None }),
+            _ => {}
+        }
+    }
+    GameRecord { meta: state.meta.clone(), mission: state.mission.clone(), seed: state.seed, actions }
+}
+
+fn cable_value(state: &GameState, player: u32, position: u32) -> u32 {
+    get_value(get_cables(&state.hands[player as usize])[position as usize], &state.all_cables)
+}
+
+/// Writes a game record in the text notation. `outcome` is written as the result of the game.
+pub fn format_record(record: &GameRecord, outcome: Option<GameOutcome>) -> String {
+    let meta = &record.meta;
+    let mut text = String::new();
+    text.push_str(&format!("[Players \"{}\"]\n", meta.players));
+    text.push_str(&format!("[BlueMax \"{}\"]\n", meta.blue_max));
+    text.push_str(&format!("[Red \"{}/{}\"]\n", meta.red_keep, meta.red_show));
+    text.push_str(&format!("[Yellow \"{}/{}\"]\n", meta.yellow_keep, meta.yellow_show));
+    match &record.mission {
+        Some(mission) if get_catalogue_mission(mission.number).as_ref() == Some(mission) => {
+            text.push_str(&format!("[Mission \"{}\"]\n", mission.number));
+        }
+        Some(mission) => {
+            let data = serde_json::to_string(mission).expect("Mission can be serialized");
+            text.push_str(&format!("[MissionData \"{}\"]\n", data));
+        }
+        None => {}
+    }
+    if meta.casual {
+        text.push_str("[Casual \"yes\"]\n");
+    }
+    text.push_str(&format!("[Seed \"{}\"]\n", record.seed));
+    let result = match outcome {
+        Some(outcome) => format!("{:?}", outcome),
+        None => "Ongoing".to_string(),
+    };
+    text.push_str(&format!("[Result \"{}\"]\n\n", result));
+    for action in &record.actions {
+        text.push_str(&format!("{}. {}\n", action.turn, format_record_action(action)));
+    }
+    text
+}
+
+/// Formats a single recorded action, without its turn number.
+pub fn format_record_action(action: &RecordedAction) -> String {
+    let value = action.value.map(format_value).unwrap_or_default();
+    let result = match action.success {
+        Some(true) => "ok",
+        _ => "fail",
+    };
+    match action.event {
+        GameEvent::DualCut { player, teammate, position_self, position_teammate } => {
+            format!("P{}#{} dual {} -> P{}#{} {}", player, position_self, value, teammate, position_teammate, result)
+        }
+        GameEvent::DoubleDetectorCut { player, teammate, position_self, positions_teammate } => format!(
+            "P{}#{} double {} -> P{}#{}/#{} {}",
+            player, position_self, value, teammate, positions_teammate.0, positions_teammate.1, result
+        ),
+        GameEvent::SoloCut { player, value } => format!("P{} solo {}", player, format_value(value)),
+        GameEvent::TokenPlaced { player, position } => format!("P{} token #{}", player, position),
+        GameEvent::LabelPlaced { player, position, kind } => {
+            let symbol = match kind {
+                LabelKind::Equal => "=",
+                LabelKind::Different => "≠",
+            };
+            format!("P{} label {} #{}", player, symbol, position)
+        }
+        GameEvent::Rewound { player } => format!("P{} rewind", player),
+        GameEvent::PostIt { player, position } => format!("P{} post-it #{}", player, position),
+        GameEvent::GeneralRadar { player, value } => format!("P{} radar {}", player, format_value(value)),
+        GameEvent::RedRevealed { player } => format!("P{} reveal-red", player),
+        _ => String::new(),
+    }
+}
+
+/// Formats a cable value like it is printed on the cable, e.g. 70 as "7" and 11 as "1.1".
+pub fn format_value(value: u32) -> String {
+    match value % 10 {
+        0 => (value / 10).to_string(),
+        rest => format!("{}.{}", value / 10, rest),
+    }
+}
+
+/// Parses a cable value as printed on the cable, e.g. "7" as 70 and "1.1" as 11.
+pub fn parse_value(text: &str) -> Result<u32, String> {
+    let invalid = || format!("'{}' is not a cable value", text);
+    match text.split_once('.') {
+        Some((whole, rest)) if rest.len() == 1 => {
+            Ok(whole.parse::<u32>().map_err(|_| invalid())? * 10 + rest.parse::<u32>().map_err(|_| invalid())?)
+        }
+        Some(_) => Err(invalid()),
+        None => Ok(text.parse::<u32>().map_err(|_| invalid())? * 10),
+    }
+}
+
+/// Parses a game record from the text notation.
+pub fn parse_record(text: &str) -> Result<GameRecord, String> {
+    let mut players = None;
+    let mut blue_max = None;
+    let mut red = None;
+    let mut yellow = None;
+    let mut mission = None;
+    let mut casual = false;
+    let mut seed = None;
+    let mut actions = vec![];
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let line_error = |e: String| format!("Line {}: {}", i + 1, e);
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+        if line.starts_with('[') {
+            let (key, value) = parse_tag(line).map_err(line_error)?;
+            match key {
+                "Players" => players = Some(parse_number(value).map_err(line_error)?),
+                "BlueMax" => blue_max = Some(parse_number(value).map_err(line_error)?),
+                "Red" => red = Some(parse_keep_show(value).map_err(line_error)?),
+                "Yellow" => yellow = Some(parse_keep_show(value).map_err(line_error)?),
+                "Mission" => {
+                    let number = parse_number(value).map_err(line_error)?;
+                    mission = Some(get_catalogue_mission(number).ok_or(line_error(format!("There is no mission {}", number)))?);
+                }
+                "MissionData" => mission = Some(parse_mission(value).map_err(line_error)?),
+                "Casual" => casual = value == "yes",
+                "Seed" => seed = Some(value.parse::<u64>().map_err(|_| line_error(format!("'{}' is not a seed", value)))?),
+                _ => {}
+            }
+            continue;
+        }
+        actions.push(parse_action(line).map_err(line_error)?);
+    }
+
+    let missing = |tag: &str| format!("The record has no {} tag", tag);
+    let (red_keep, red_show) = red.ok_or(missing("Red"))?;
+    let (yellow_keep, yellow_show) = yellow.ok_or(missing("Yellow"))?;
+    let mut meta = GameMeta::try_new(
+        blue_max.ok_or(missing("BlueMax"))?,
+        red_show,
+        red_keep,
+        yellow_show,
+        yellow_keep,
+        players.ok_or(missing("Players"))?,
+    )?;
+    meta.casual = casual;
+    Ok(GameRecord { meta, mission, seed: seed.ok_or(missing("Seed"))?, actions })
+}
+
+fn parse_tag(line: &str) -> Result<(&str, &str), String> {
+    let inner = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')).ok_or("A tag has to be enclosed in [ and ]")?;
+    let (key, value) = inner.split_once(' ').ok_or("A tag needs a name and a value")?;
+    let value = value.trim().strip_prefix('"').and_then(|v| v.strip_suffix('"')).ok_or("A tag value has to be quoted")?;
+    Ok((key, value))
+}
+
+fn parse_number(text: &str) -> Result<u32, String> {
+    text.parse::<u32>().map_err(|_| format!("'{}' is not a number", text))
+}
+
+fn parse_keep_show(text: &str) -> Result<(u32, u32), String> {
+    let (keep, show) = text.split_once('/').ok_or(format!("'{}' has to be written as kept/shown", text))?;
+    Ok((parse_number(keep)?, parse_number(show)?))
+}
+
+/// Parses a player with an optional position, e.g. "P1" or "P1#4".
+fn parse_player(text: &str) -> Result<(u32, Option<u32>), String> {
+    let text = text.strip_prefix('P').ok_or(format!("'{}' is not a player", text))?;
+    match text.split_once('#') {
+        Some((player, position)) => Ok((parse_number(player)?, Some(parse_number(position)?))),
+        None => Ok((parse_number(text)?, None)),
+    }
+}
+
+fn parse_position(text: &str) -> Result<u32, String> {
+    parse_number(text.strip_prefix('#').ok_or(format!("'{}' is not a position", text))?)
+}
+
+fn parse_success(text: Option<&str>) -> Result<bool, String> {
+    match text {
+        Some("ok") => Ok(true),
+        Some("fail") => Ok(false),
+        _ => Err("A cut has to end with ok or fail".to_string()),
+    }
+}
+
+fn parse_action(line: &str) -> Result<RecordedAction, String> {
+    let (turn, rest) = line.split_once(". ").ok_or("An action has to start with the turn number")?;
+    let turn = parse_number(turn)?;
+    let words: Vec<&str> = rest.split_whitespace().collect();
+    let (player, position) = parse_player(words.first().ok_or("The action is empty")?)?;
+    let word = |i: usize| words.get(i).copied().ok_or(format!("The action '{}' is incomplete", rest));
+    let own_position = || position.ok_or(format!("A cut needs the position of the own cable, e.g. P{}#3", player));
+
+    let (event, value, success) = match word(1)? {
+        "dual" => {
+            let value = parse_value(word(2)?)?;
+            if word(3)? != "->" {
+                return Err("Expected -> before the teammate's cable".to_string());
+            }
+            let (teammate, position_teammate) = parse_player(word(4)?)?;
+            let position_teammate = position_teammate.ok_or("A dual cut needs the position of the teammate's cable")?;
+            let success = parse_success(words.get(5).copied())?;
+            (GameEvent::DualCut { player, teammate, position_self: own_position()?, position_teammate }, Some(value), Some(success))
+        }
+        "double" => {
+            let value = parse_value(word(2)?)?;
+            if word(3)? != "->" {
+                return Err("Expected -> before the teammate's cables".to_string());
+            }
+            let (targets, second) = word(4)?.split_once('/').ok_or("A double detector cut needs two positions, e.g. P1#3/#4")?;
+            let (teammate, first) = parse_player(targets)?;
+            let first = first.ok_or("A double detector cut needs two positions, e.g. P1#3/#4")?;
+            let success = parse_success(words.get(5).copied())?;
+            let event = GameEvent::DoubleDetectorCut { player, teammate, position_self: own_position()?, positions_teammate: (first, parse_position(second)?) };
+            (event, Some(value), Some(success))
+        }
+        "solo" => (GameEvent::SoloCut { player, value: parse_value(word(2)?)? }, None, None),
+        "token" => (GameEvent::TokenPlaced { player, position: parse_position(word(2)?)? }, None, None),
+        "label" => {
+            let kind = match word(2)? {
+                "=" => LabelKind::Equal,
+                "≠" | "!=" => LabelKind::Different,
+                other => return Err(format!("'{}' is not a label, use = or ≠", other)),
+            };
+            (GameEvent::LabelPlaced { player, position: parse_position(word(3)?)?, kind }, None, None)
+        }
+        "rewind" => (GameEvent::Rewound { player }, None, None),
+        "post-it" => (GameEvent::PostIt { player, position: parse_position(word(2)?)? }, None, None),
+        "radar" => (GameEvent::GeneralRadar { player, value: parse_value(word(2)?)? }, None, None),
+        "reveal-red" => (GameEvent::RedRevealed { player }, None, None),
+        other => return Err(format!("'{}' is not an action", other)),
+    };
+    Ok(RecordedAction { turn, event, value, success })
+}
+
+/// Deals the game of a record, before its info tokens and first action.
+fn start_record(record: &GameRecord) -> GameState {
+    GameState::deal(record.meta.clone(), record.mission.clone(), record.seed)
+}
+
+/// Plays a recorded action and checks that it leads to the recorded result.
+pub fn apply_recorded_action(state: &mut GameState, action: &RecordedAction) -> Result<(), String> {
+    if let GameEvent::TokenPlaced { .. } = action.event {
+        if action.turn != 0 {
+            return Err("Info tokens are placed before the first turn, as turn 0".to_string());
+        }
+        return record_event(state, action.event.clone());
+    }
+    if action.turn != state.turn {
+        return Err(format!("The action is recorded for turn {}, but the game is in turn {}", action.turn, state.turn));
+    }
+    let mismatch = || "The record does not match the deal of its seed".to_string();
+    match action.event {
+        GameEvent::DualCut { player, teammate, position_self, position_teammate } => {
+            check_current_player(state, player)?;
+            check_recorded_value(state, player, position_self, action.value)?;
+            let success = play_dual_cut(state, teammate, position_self, position_teammate)?;
+            if Some(success) != action.success {
+                return Err(mismatch());
+            }
+        }
+        GameEvent::DoubleDetectorCut { player, teammate, position_self, positions_teammate } => {
+            check_current_player(state, player)?;
+            check_recorded_value(state, player, position_self, action.value)?;
+            let success = play_double_detector_cut(state, teammate, position_self, positions_teammate)?;
+            if Some(success) != action.success {
+                return Err(mismatch());
+            }
+        }
+        GameEvent::SoloCut { player, value } => {
+            check_current_player(state, player)?;
+            play_solo_cut(state, value)?;
+        }
+        GameEvent::RedRevealed { player } => {
+            check_current_player(state, player)?;
+            play_reveal_red(state)?;
+        }
+        _ => record_event(state, action.event.clone())?,
+    }
+    Ok(())
+}
+
+fn check_current_player(state: &GameState, player: u32) -> Result<(), String> {
+    if player != state.current_player {
+        return Err(format!("It is player {}'s turn, not player {}'s", state.current_player, player));
+    }
+    Ok(())
+}
+
+fn check_recorded_value(state: &GameState, player: u32, position: u32, value: Option<u32>) -> Result<(), String> {
+    let cables = get_cables(&state.hands[player as usize]);
+    match cables.get(position as usize) {
+        Some(id) if Some(get_value(*id, &state.all_cables)) == value => Ok(()),
+        _ => Err("The record does not match the deal of its seed".to_string()),
+    }
+}
+
+/// Replays a whole record and returns the final state and its outcome. `step` is called after every action.
+pub fn replay_record(record: &GameRecord, mut step: impl FnMut(&RecordedAction, &GameState)) -> Result<(GameState, Option<GameOutcome>), String> {
+    let mut state = start_record(record);
+    for (i, action) in record.actions.iter().enumerate() {
+        apply_recorded_action(&mut state, action).map_err(|e| format!("Action {}: {}", i + 1, e))?;
+        step(action, &state);
+    }
+    let outcome = get_outcome(&state.hands, &state.all_cables, state.death_counter);
+    Ok((state, outcome))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::new_game;
+    use crate::mission::mission_game_meta;
+    use crate::player::{CableStatus, get_status};
+
+    /// Plays a few dual cuts, the first uncut cable of the current player on any cable of the next player.
+    fn play_some_turns(state: &mut GameState, turns: usize) {
+        for _ in 0..turns {
+            if get_outcome(&state.hands, &state.all_cables, state.death_counter).is_some() {
+                return;
+            }
+            let player = state.current_player;
+            let teammate = (player + 1) % state.meta.players;
+            let uncut = |hand| get_status(hand).iter().position(|s| *s != CableStatus::Revealed).unwrap() as u32;
+            let position_self = uncut(&state.hands[player as usize]);
+            let position_teammate = uncut(&state.hands[teammate as usize]);
+            play_dual_cut(state, teammate, position_self, position_teammate).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_export_and_import_record() {
+        let mut state = new_game(GameMeta::new(12, 2, 1, 3, 2, 3), None, 21);
+        record_event(&mut state, GameEvent::Rewound { player: 2 }).ok();
+        play_some_turns(&mut state, 3);
+
+        let record = record_from_state(&state);
+        let outcome = get_outcome(&state.hands, &state.all_cables, state.death_counter);
+        let text = format_record(&record, outcome);
+        let parsed = parse_record(&text).expect("Exported record should be parsed");
+        assert_eq!(parsed, record, "Expected the parsed record to match the exported one");
+
+        let mut steps = 0;
+        let (replayed, replayed_outcome) = replay_record(&parsed, |_, _| steps += 1).expect("Record should be replayed");
+        assert_eq!(steps, record.actions.len(), "Expected one step per action");
+        assert_eq!(replayed_outcome, outcome, "Expected the same outcome after replay");
+        assert_eq!(replayed.death_counter, state.death_counter, "Expected the same detonator after replay");
+        for (hand, replayed_hand) in state.hands.iter().zip(&replayed.hands) {
+            assert_eq!(get_status(hand), get_status(replayed_hand), "Expected the same cable status after replay");
+        }
+    }
+
+    #[test]
+    fn test_token_in_the_middle_of_a_record() {
+        let mut state = new_game(GameMeta::new(12, 2, 1, 3, 2, 3), None, 21);
+        play_some_turns(&mut state, 2);
+        let text = format_record(&record_from_state(&state), None);
+        let second_turn = text.find("\n2. ").expect("Expected a second turn") + 1;
+        let position = get_status(&state.hands[1]).iter().position(|s| *s == CableStatus::Hidden).unwrap();
+        let text = format!("{}0. P1 token #{}\n{}", &text[..second_turn], position, &text[second_turn..]);
+        let parsed = parse_record(&text).expect("Record should be parsed");
+        assert!(replay_record(&parsed, |_, _| {}).is_err(), "Expected an info token in the middle of the game to be rejected");
+    }
+
+    #[test]
+    fn test_record_keeps_custom_mission() {
+        let data = r#"{"number": 42, "name": "Workshop", "blue_max": 8, "red_show": 1, "red_keep": 1, "yellow_show": 0, "yellow_keep": 0, "detonator_start": 2}"#;
+        let mission = parse_mission(data).unwrap();
+        let mut state = new_game(mission_game_meta(&mission, 3).unwrap(), Some(mission.clone()), 5);
+        play_some_turns(&mut state, 2);
+
+        let text = format_record(&record_from_state(&state), None);
+        assert!(text.contains("[MissionData "), "Expected a mission outside the catalogue to be written out");
+        let parsed = parse_record(&text).expect("Record with a mission file should be parsed");
+        assert_eq!(parsed.mission, Some(mission), "Expected the mission to be read back");
+        let (replayed, _) = replay_record(&parsed, |_, _| {}).expect("Record should be replayed");
+        assert_eq!(replayed.death_counter, state.death_counter, "Expected the detonator of the mission after replay");
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let text = "[Players \"2\"]\n[BlueMax \"8\"]\n[Red \"0/0\"]\n[Yellow \"2/2\"]\n[Seed \"7\"]\n\n; comment\n1. P0#3 dual 1.1 -> P1#4 fail\n2. P1 label != #2\n2. P1 solo 7\n";
+        let record = parse_record(text).expect("Record should be parsed");
+        assert_eq!((record.meta.players, record.meta.yellow_keep, record.seed), (2, 2, 7), "Expected the tags to be parsed");
+        assert_eq!(record.actions.len(), 3, "Expected 3 actions, found {}", record.actions.len());
+        assert_eq!(record.actions[0].event, GameEvent::DualCut { player: 0, teammate: 1, position_self: 3, position_teammate: 4 });
+        assert_eq!((record.actions[0].value, record.actions[0].success), (Some(11), Some(false)));
+        assert_eq!(record.actions[1].event, GameEvent::LabelPlaced { player: 1, position: 2, kind: LabelKind::Different });
+        assert_eq!(record.actions[2].event, GameEvent::SoloCut { player: 1, value: 70 });
+    }
+
+    #[test]
+    fn test_parse_invalid_record() {
+        assert!(parse_record("[Players \"2\"]").is_err(), "Expected missing tags to be rejected");
+        let header = "[Players \"2\"]\n[BlueMax \"8\"]\n[Red \"0/0\"]\n[Yellow \"0/0\"]\n[Seed \"7\"]\n";
+        assert!(parse_record(&format!("{}1. P0 dual 3 -> P1#2 ok", header)).is_err(), "Expected a dual cut without own position to be rejected");
+        assert!(parse_record(&format!("{}1. P0#1 cut 3", header)).is_err(), "Expected an unknown action to be rejected");
+        assert!(parse_record(&format!("{}1. P0#1 dual 3 -> P1#2", header)).is_err(), "Expected a dual cut without result to be rejected");
+    }
+
+    #[test]
+    fn test_format_and_parse_value() {
+        for value in [10, 70, 120, 11, 25] {
+            assert_eq!(parse_value(&format_value(value)), Ok(value), "Expected value {} to survive formatting", value);
+        }
+        assert!(parse_value("1.25").is_err(), "Expected two decimals to be rejected");
+        assert!(parse_value("x").is_err(), "Expected text to be rejected");
+    }
+}
@@ -0,0 +1,158 @@
+//! This module parses the game settings given on the command line, e.g. `--players 4 --mission 3 --seed 42`.
+//! The setup wizard only asks for the settings which are not given, so a game with all settings starts without prompts.
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::game::{MAX_PLAYERS, MIN_PLAYERS};
+use crate::mission::{Mission, get_catalogue_mission, load_mission};
+use crate::montecarlo::Budget;
+use crate::seat::{Seat, parse_seats};
+
+/// Settings given on the command line. `None` if a setting is not given.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GameOptions {
+    pub players: Option<u32>,
+    pub blue_max: Option<u32>,
+    pub red_show: Option<u32>,
+    pub red_keep: Option<u32>,
+    pub yellow_show: Option<u32>,
+    pub yellow_keep: Option<u32>,
+    pub seed: Option<u64>,
+    pub mission: Option<Mission>,
+    pub seats: Option<Vec<Seat>>,
+    pub casual: Option<bool>,
+    /// Several humans share the terminal.
+    pub hot_seat: Option<bool>,
+    /// Estimates the difficulty with bot games before the game starts.
+    pub difficulty: Option<bool>,
+    /// Iterations of the Monte Carlo bots per move.
+    pub mc_iterations: Option<usize>,
+    /// Time limit of the Monte Carlo bots per move in seconds, 0 for no limit.
+    pub mc_time: Option<f64>,
+}
+
+/// Returns whether any cable is set, which makes the game a custom game.
+pub fn has_cable_options(options: &GameOptions) -> bool {
+    [options.blue_max, options.red_show, options.red_keep, options.yellow_show, options.yellow_keep].iter().any(|c| c.is_some())
+}
+
+/// Parses the settings of a game. Every option takes a value, e.g. `--seats hbm` or `--casual yes`.
+pub fn parse_game_options(args: &[String]) -> Result<GameOptions, String> {
+    let mut options = GameOptions::default();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or(format!("Please give a value after {}", flag))?;
+        let number = || value.parse::<u32>().map_err(|_| format!("'{}' is not a number for {}", value, flag));
+        match flag.as_str() {
+            "--players" => options.players = Some(number()?),
+            "--blue-max" => options.blue_max = Some(number()?),
+            "--red-show" => options.red_show = Some(number()?),
+            "--red-keep" => options.red_keep = Some(number()?),
+            "--yellow-show" => options.yellow_show = Some(number()?),
+            "--yellow-keep" => options.yellow_keep = Some(number()?),
+            "--seed" => options.seed = Some(value.parse::<u64>().map_err(|_| format!("'{}' is not a seed", value))?),
+            "--mission" => options.mission = Some(parse_mission_option(value)?),
+            "--seats" => options.seats = Some(parse_seats(value, value.chars().count() as u32)?),
+            "--casual" => options.casual = Some(parse_yes_no(value, flag)?),
+            "--hot-seat" => options.hot_seat = Some(parse_yes_no(value, flag)?),
+            "--difficulty" => options.difficulty = Some(parse_yes_no(value, flag)?),
+            "--mc-iterations" => options.mc_iterations = Some(number()? as usize),
+            "--mc-time" => options.mc_time = Some(value.parse::<f64>().ok().filter(|t| *t >= 0.0).ok_or(format!("'{}' is no time in seconds for {}", value, flag))?),
+            _ => return Err(format!("Unknown option {}", flag)),
+        }
+    }
+    if let (Some(players), Some(seats)) = (options.players, &options.seats)
+        && seats.len() != players as usize
+    {
+        return Err(format!("Expected {} seats for {} players, found {}", players, players, seats.len()));
+    }
+    if let Some(players) = options_players(&options)
+        && !(MIN_PLAYERS..=MAX_PLAYERS).contains(&players)
+    {
+        return Err(format!("A game needs {} to {} players, not {}", MIN_PLAYERS, MAX_PLAYERS, players));
+    }
+    if options.mission.is_some() && has_cable_options(&options) {
+        return Err("A mission sets its own cables, so --mission cannot be combined with cable options".to_string());
+    }
+    Ok(options)
+}
+
+fn parse_yes_no(value: &str, flag: &str) -> Result<bool, String> {
+    match value {
+        "yes" | "y" => Ok(true),
+        "no" | "n" => Ok(false),
+        _ => Err(format!("'{}' is not yes or no for {}", value, flag)),
+    }
+}
+
+/// A mission is given by its number in the catalogue or the path of a mission file.
+fn parse_mission_option(value: &str) -> Result<Mission, String> {
+    match value.parse::<u32>() {
+        Ok(number) => get_catalogue_mission(number).ok_or(format!("There is no mission {}", number)),
+        Err(_) => load_mission(Path::new(value)),
+    }
+}
+
+/// Returns the number of players of the settings, given directly or by the seats.
+pub fn options_players(options: &GameOptions) -> Option<u32> {
+    options.players.or(options.seats.as_ref().map(|seats| seats.len() as u32))
+}
+
+/// Returns the search budget of the Monte Carlo bots, the default budget for the settings which are not given.
+pub fn options_budget(options: &GameOptions) -> Budget {
+    let default = Budget::default();
+    Budget {
+        iterations: options.mc_iterations.unwrap_or(default.iterations),
+        time: match options.mc_time {
+            Some(seconds) if seconds > 0.0 => Some(Duration::from_secs_f64(seconds)),
+            Some(_) => None,
+            None => default.time,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str) -> Vec<String> {
+        text.split_whitespace().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_game_options() {
+        let options = parse_game_options(&args("--players 4 --mission 3 --seed 42")).unwrap();
+        assert_eq!((options.players, options.seed), (Some(4), Some(42)), "Expected the players and the seed to be parsed");
+        assert_eq!(options.mission.map(|m| m.number), Some(3), "Expected mission 3 from the catalogue");
+        assert_eq!(parse_game_options(&[]), Ok(GameOptions::default()), "Expected no settings without arguments");
+
+        assert_eq!(parse_game_options(&args("--hot-seat yes")).unwrap().hot_seat, Some(true), "Expected hot-seat mode to be switched on");
+        assert_eq!(parse_game_options(&args("--difficulty no")).unwrap().difficulty, Some(false), "Expected the difficulty estimate to be switched off");
+        assert!(parse_game_options(&args("--mc-time -1")).is_err(), "Expected a negative time to be rejected");
+        assert!(parse_game_options(&args("--casual maybe")).is_err(), "Expected a value which is not yes or no to be rejected");
+        assert!(parse_game_options(&args("--players")).is_err(), "Expected a missing value to be rejected");
+        assert!(parse_game_options(&args("--players four")).is_err(), "Expected a value which is no number to be rejected");
+        assert!(parse_game_options(&args("--speed 3")).is_err(), "Expected an unknown option to be rejected");
+        assert!(parse_game_options(&args("--players 3 --seats hb")).is_err(), "Expected seats for the wrong number of players to be rejected");
+        assert!(parse_game_options(&args("--seats h")).is_err(), "Expected a single seat to be rejected");
+        assert!(parse_game_options(&args("--mission 3 --blue-max 8")).is_err(), "Expected cables next to a mission to be rejected");
+    }
+
+    #[test]
+    fn test_options_budget() {
+        assert_eq!(options_budget(&GameOptions::default()), Budget::default(), "Expected the default budget without settings");
+        let budget = options_budget(&parse_game_options(&args("--mc-iterations 30 --mc-time 0.5")).unwrap());
+        assert_eq!(budget, Budget { iterations: 30, time: Some(Duration::from_millis(500)) }, "Expected the given iterations and time");
+        assert_eq!(options_budget(&parse_game_options(&args("--mc-time 0")).unwrap()).time, None, "Expected no time limit for 0 seconds");
+    }
+
+    #[test]
+    fn test_options_players() {
+        let options = parse_game_options(&args("--seats hbb --blue-max 10")).unwrap();
+        assert_eq!(options.seats, Some(vec![Seat::Human, Seat::Bot, Seat::Bot]), "Expected the given seats");
+        assert_eq!(options_players(&options), Some(3), "Expected the number of players from the seats");
+        assert!(has_cable_options(&options), "Expected a cable option");
+        assert_eq!(options_players(&GameOptions::default()), None, "Expected no number of players without settings");
+    }
+}
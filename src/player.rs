@@ -1,51 +1,62 @@
 use std::collections::{HashSet, HashMap};
-use std::sync::atomic::{AtomicU32, Ordering};
 use rand::prelude::*;
-use rust_helpers::{split_rand_hashset_eq};
-
-use crate::elements::{init_all_cables, init_cables_in_game, get_value, get_color};
-
-pub fn init_cable_distribution(in_game_cables: &HashSet<u32>, number_players: u32) -> Vec<Vec<u32>> {
-    let mut distributions: Vec<Vec<u32>> = split_rand_hashset_eq(in_game_cables.clone(), number_players as usize);
+use serde::{Deserialize, Serialize};
+
+/// Deals the cables in game randomly and evenly to the players.
+pub fn init_cable_distribution(in_game_cables: &HashSet<u32>, number_players: u32, rng: &mut impl Rng) -> Vec<Vec<u32>> {
+    // The IDs are sorted before shuffling, so that the same RNG state always deals the same hands.
+    let mut cable_ids: Vec<u32> = in_game_cables.iter().copied().collect();
+    cable_ids.sort();
+    cable_ids.shuffle(rng);
+    let mut distributions: Vec<Vec<u32>> = vec![vec![]; number_players as usize];
+    for (i, cable_id) in cable_ids.into_iter().enumerate() {
+        distributions[i % number_players as usize].push(cable_id);
+    }
 
-    println!("Hands initialized.");
-    return distributions
+    distributions
 }
 
-pub fn sort_cable_distribution(distributions: &mut Vec<Vec<u32>>, all_cables: &HashMap<u32, u32>) {
+pub fn sort_cable_distribution(distributions: &mut [Vec<u32>], all_cables: &HashMap<u32, u32>) {
     for distribution in distributions.iter_mut() {
         distribution.sort_by_key(|id| all_cables.get(id).cloned().unwrap_or(0));
     }
-        println!("Distribution sorted.");
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Hand {
     cables: Vec<u32>, 
     status: Vec<CableStatus>,
-    announced: Vec<u32>
-}
-
-impl Hand {
-    pub fn new(cables: Vec<u32>) -> Self {
-        let status = vec![CableStatus::Hidden; cables.len()];
-        Hand { cables, status, announced: vec![] }
-    }
+    announced: Vec<u32>,
+    labels: Vec<Label>
 }
 
-#[derive(Clone, Debug, PartialEq, Copy)]
+#[derive(Clone, Debug, PartialEq, Copy, Serialize, Deserialize)]
 pub enum CableStatus {
     Hidden,
     Clue,
     Revealed,
 }
 
-pub fn get_cables(hand: &Hand) -> Vec<u32> {
-    hand.cables.clone()
+/// A public label between two adjacent cables of a hand.
+/// `position` is the index of the left cable, the label sits between `position` and `position + 1`.
+#[derive(Clone, Debug, PartialEq, Copy, Serialize, Deserialize)]
+pub struct Label {
+    pub position: usize,
+    pub kind: LabelKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Copy, Serialize, Deserialize)]
+pub enum LabelKind {
+    Equal,
+    Different,
+}
+
+pub fn get_cables(hand: &Hand) -> &[u32] {
+    &hand.cables
 }
 
-pub fn get_status(hand: &Hand) -> Vec<CableStatus> {
-    hand.status.clone()
+pub fn get_status(hand: &Hand) -> &[CableStatus] {
+    &hand.status
 }
 
 pub fn init_hands(sorted_cable_distribution: Vec<Vec<u32>>) -> Vec<Hand> {
@@ -54,24 +65,12 @@ pub fn init_hands(sorted_cable_distribution: Vec<Vec<u32>>) -> Vec<Hand> {
             cables: cables.clone(),
             status: vec![CableStatus::Hidden; cables.len()],
             announced: vec![],
+            labels: vec![],
         }
     }).collect()
 }
 
-pub fn get_teammate_hands(player_number: u32, hands: &Vec<Hand>) -> Vec<Hand> {
-    let teammate_hands: Vec<Hand> = hands.iter()
-        .enumerate()
-        .filter(|(index, _)| *index as u32 != player_number)
-        .map(|(_, hand)| hand.clone())
-        .collect();
-
-    for hand in &teammate_hands {       
-    println!("Teammate's hand {:?}", hand);
-    }
-    return teammate_hands
-}
-
-pub fn change_cable_status(cable_id: u32, hands: &mut Vec<Hand>, new_status: CableStatus) {
+pub fn change_cable_status(cable_id: u32, hands: &mut [Hand], new_status: CableStatus) {
     for hand in hands.iter_mut() {
         if let Some(pos) = hand.cables.iter().position(|&id| id == cable_id) {
             hand.status[pos] = new_status;
@@ -79,8 +78,8 @@ pub fn change_cable_status(cable_id: u32, hands: &mut Vec<Hand>, new_status: Cab
     }
 }
 
-pub fn get_announced_cables(hand: &Hand) -> Vec<u32> {
-    hand.announced.clone()
+pub fn get_announced_cables(hand: &Hand) -> &[u32] {
+    &hand.announced
 }
 
 pub fn add_announced_cable(hand: &mut Hand, cable_id: u32) {
@@ -89,15 +88,31 @@ pub fn add_announced_cable(hand: &mut Hand, cable_id: u32) {
     }
 }
 
+pub fn get_labels(hand: &Hand) -> &[Label] {
+    &hand.labels
+}
+
+/// Returns the label between `position` and `position + 1`, if there is one.
+pub fn get_label_at(hand: &Hand, position: usize) -> Option<Label> {
+    hand.labels.iter().find(|label| label.position == position).copied()
+}
+
+pub fn add_label(hand: &mut Hand, label: Label) {
+    if get_label_at(hand, label.position).is_none() {
+        hand.labels.push(label);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::elements::{get_value, init_all_cables, init_cables_in_game};
 
     #[test]
     fn test_cable_distribution() {
         let all_cables = init_all_cables(10, 3, 2);
-        let in_game_cables = init_cables_in_game(&all_cables, 2, 1);
-        let hands: Vec<Vec<u32>> = init_cable_distribution(&in_game_cables, 3);
+        let in_game_cables = init_cables_in_game(&all_cables, 2, 1, &mut rand::rng());
+        let hands: Vec<Vec<u32>> = init_cable_distribution(&in_game_cables, 3, &mut rand::rng());
         assert!(!hands.is_empty(), "Hands should not be empty");
         let distribution = hands.iter().map(|hand| hand.len()).collect::<HashSet<_>>();
         assert!(distribution.iter().max().unwrap() - distribution.iter().min().unwrap() <= 1, "Hands should be evenly distributed");
@@ -106,8 +121,8 @@ mod tests {
     #[test]
     fn test_sort_cable_distribution() {
         let all_cables = init_all_cables(10, 3, 2);
-        let in_game_cables = init_cables_in_game(&all_cables, 2, 1);
-        let mut cable_distributions: Vec<Vec<u32>> = init_cable_distribution(&in_game_cables, 3);
+        let in_game_cables = init_cables_in_game(&all_cables, 2, 1, &mut rand::rng());
+        let mut cable_distributions: Vec<Vec<u32>> = init_cable_distribution(&in_game_cables, 3, &mut rand::rng());
         assert!(!cable_distributions.is_empty(), "Hands should not be empty");
 
         sort_cable_distribution(&mut cable_distributions, &all_cables);
@@ -120,8 +135,8 @@ mod tests {
     #[test]
     fn test_init_hands() {
         let all_cables = init_all_cables(10, 3, 2);
-        let in_game_cables = init_cables_in_game(&all_cables, 2, 1);
-        let cable_distributions: Vec<Vec<u32>> = init_cable_distribution(&in_game_cables, 3);
+        let in_game_cables = init_cables_in_game(&all_cables, 2, 1, &mut rand::rng());
+        let cable_distributions: Vec<Vec<u32>> = init_cable_distribution(&in_game_cables, 3, &mut rand::rng());
         assert!(!cable_distributions.is_empty(), "Hands should not be empty");
 
         let mut sorted_hands = cable_distributions.clone();
@@ -135,23 +150,6 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_get_teammate_hands() {
-        let all_cables = init_all_cables(10, 3, 2);
-        let in_game_cables = init_cables_in_game(&all_cables, 2, 1);
-        let cable_distributions: Vec<Vec<u32>> = init_cable_distribution(&in_game_cables, 3);
-        assert!(!cable_distributions.is_empty(), "Hands should not be empty");
-
-        let mut sorted_hands = cable_distributions.clone();
-        sort_cable_distribution(&mut sorted_hands, &all_cables);
-        let initialized_hands = init_hands(sorted_hands);
-        
-        assert_eq!(initialized_hands.len(), cable_distributions.len(), "Number of cable_distributions should match");
-        let current_player = 1;
-
-        get_teammate_hands(current_player, &initialized_hands);
-    }
-
     #[test]
     fn test_change_cable_status() {
         let mut hands = vec![
@@ -159,11 +157,13 @@ mod tests {
                 cables: vec![1, 2, 3],
                 status: vec![CableStatus::Clue, CableStatus::Hidden, CableStatus::Revealed],
                 announced: vec![],
+                labels: vec![],
             },
             Hand {
                 cables: vec![4, 5, 6],
                 status: vec![CableStatus::Hidden; 3],
                 announced: vec![],
+                labels: vec![],
             },
         ];
         change_cable_status(2, &mut hands, CableStatus::Revealed);
@@ -173,4 +173,14 @@ mod tests {
         //let mut all_cables: HashMap<u32, u32> = HashMap::new();
         //all_cables.extend([(1, 10), (2, 25), (3, 30), (4, 40), (5, 51), (6, 60)]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_add_label() {
+        let mut hand = init_hands(vec![vec![1, 2, 3]]).remove(0);
+        add_label(&mut hand, Label { position: 0, kind: LabelKind::Equal });
+        add_label(&mut hand, Label { position: 0, kind: LabelKind::Different });
+        assert_eq!(get_labels(&hand).len(), 1, "Only one label should be placed between the same two cables");
+        assert_eq!(get_label_at(&hand, 0).map(|label| label.kind), Some(LabelKind::Equal), "The first label should be kept");
+        assert!(get_label_at(&hand, 1).is_none(), "There should be no label between the second and third cable");
+    }
+}
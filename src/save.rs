@@ -0,0 +1,121 @@
+//! This module saves games to disk and loads them again.
+//! A save file is a versioned JSON document containing the full game state, including the state of the RNG.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{GameState, validate_meta};
+use crate::player::{get_cables, get_status};
+
+/// Version of the save file format. Increase it whenever `GameState` changes incompatibly.
+pub const SAVE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    version: u32,
+    state: GameState,
+}
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+pub fn save_game(state: &GameState, path: &Path) -> Result<(), String> {
+    let save_file = SaveFile { version: SAVE_VERSION, state: state.clone() };
+    let data = serde_json::to_string_pretty(&save_file).map_err(|e| e.to_string())?;
+    fs::write(path, data).map_err(|e| format!("Could not write save file {}: {}", path.display(), e))
+}
+
+/// Loads a game for `players` players. Fails if the file is no consistent save of such a game.
+pub fn load_game(path: &Path, players: u32) -> Result<GameState, String> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read save file {}: {}", path.display(), e))?;
+    let header: SaveHeader = serde_json::from_str(&data)
+        .map_err(|e| format!("Invalid save file {}: {}", path.display(), e))?;
+    if header.version != SAVE_VERSION {
+        return Err(format!("Save file version {} is not supported, expected version {}", header.version, SAVE_VERSION));
+    }
+    let save_file: SaveFile = serde_json::from_str(&data)
+        .map_err(|e| format!("Invalid save file {}: {}", path.display(), e))?;
+    validate_meta(&save_file.state.meta).map_err(|e| format!("Invalid save file {}: {}", path.display(), e))?;
+    check_state(&save_file.state).map_err(|e| format!("Invalid save file {}: {}", path.display(), e))?;
+    if save_file.state.meta.players != players {
+        return Err(format!("The saved game has {} players, this game has {}", save_file.state.meta.players, players));
+    }
+    Ok(save_file.state)
+}
+
+/// Checks that the racks, characters and current player of a loaded state fit its number of players.
+fn check_state(state: &GameState) -> Result<(), String> {
+    let players = state.meta.players as usize;
+    if state.hands.len() != players || state.characters.len() != players {
+        return Err(format!("Expected {} racks and characters, found {} and {}", players, state.hands.len(), state.characters.len()));
+    }
+    if state.hands.iter().any(|hand| get_cables(hand).len() != get_status(hand).len()) {
+        return Err("Every cable of a rack needs exactly one status".to_string());
+    }
+    if state.current_player as usize >= players {
+        return Err(format!("There is no player {}", state.current_player));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::new_game;
+    use std::env;
+    use rand::Rng;
+    use crate::actions::duo_cut;
+    use crate::game::GameMeta;
+
+    #[test]
+    fn test_save_and_load_game() {
+        let path = env::temp_dir().join(format!("bomb-busters-save-test-{}.json", std::process::id()));
+        let mut state = new_game(GameMeta::new(10, 2, 1, 2, 1, 3), None, 3);
+        duo_cut(0, 1, 0, 0, &mut state.hands, &state.all_cables);
+        state.death_counter -= 1;
+        state.turn += 1;
+
+        save_game(&state, &path).expect("Game should be saved");
+        let mut loaded = load_game(&path, 3).expect("Game should be loaded");
+        assert!(load_game(&path, 4).is_err(), "Expected a save with another number of players to be rejected");
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.all_cables, state.all_cables, "Expected the same cables after loading");
+        for (hand, loaded_hand) in state.hands.iter().zip(&loaded.hands) {
+            assert_eq!(get_cables(hand), get_cables(loaded_hand), "Expected the same hands after loading");
+            assert_eq!(get_status(hand), get_status(loaded_hand), "Expected the same cable status after loading");
+        }
+        assert_eq!((loaded.death_counter, loaded.turn, loaded.current_player), (state.death_counter, state.turn, state.current_player), "Expected the same progress after loading");
+        assert_eq!(loaded.rng.random::<u64>(), state.rng.random::<u64>(), "Expected the RNG to continue identically after loading");
+    }
+
+    #[test]
+    fn test_load_unsupported_version() {
+        let path = env::temp_dir().join(format!("bomb-busters-version-test-{}.json", std::process::id()));
+        fs::write(&path, r#"{"version": 999, "state": {}}"#).unwrap();
+        let result = load_game(&path, 3);
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err(), "Expected an unsupported version to be rejected");
+    }
+
+    #[test]
+    fn test_load_inconsistent_state() {
+        let path = env::temp_dir().join(format!("bomb-busters-inconsistent-test-{}.json", std::process::id()));
+        let mut state = new_game(GameMeta::new(10, 2, 1, 2, 1, 3), None, 3);
+        state.current_player = 3;
+        save_game(&state, &path).unwrap();
+        let wrong_player = load_game(&path, 3);
+        state.current_player = 0;
+        state.hands.pop();
+        save_game(&state, &path).unwrap();
+        let missing_rack = load_game(&path, 3);
+        fs::remove_file(&path).unwrap();
+        assert!(wrong_player.is_err(), "Expected a current player out of range to be rejected");
+        assert!(missing_rack.is_err(), "Expected a missing rack to be rejected");
+    }
+}
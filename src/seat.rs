@@ -0,0 +1,124 @@
+//! This module connects the players to the game. Every seat is taken by a human at the terminal or by a bot,
+//! both decide from their `PlayerView` where to place their info token and what to do on their turn.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::bot::HeuristicBot;
+use crate::elements::Equipment;
+use crate::events::*;
+use crate::game::GameState;
+use crate::montecarlo::{Budget, MonteCarloBot};
+use crate::terminal::TerminalPlayer;
+use crate::view::{PlayerView, player_view};
+
+/// Who takes a seat at the table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Seat {
+    Human,
+    Bot,
+    /// The stronger Monte Carlo bot.
+    MonteCarlo,
+}
+
+/// A move of the current player.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Move {
+    DualCut { teammate: u32, position_self: u32, position_teammate: u32 },
+    /// Dual cut on two cables of the teammate with the Double Detector ability.
+    DoubleDetectorCut { teammate: u32, position_self: u32, positions_teammate: (u32, u32) },
+    SoloCut { value: u32 },
+    /// Turns the remaining cables face up, allowed once they are all red.
+    RevealRed,
+}
+
+/// What a player does on their turn. Besides moves, humans can manage the game.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Turn {
+    Play(Move),
+    /// Uses an equipment card on the own cable at `position`, without ending the turn.
+    Use { equipment: Equipment, position: u32 },
+    /// Uses the Rewinder ability, without ending the turn.
+    Rewind,
+    /// Uses the Post-it ability on the own cable at `position`, without ending the turn.
+    PostIt { position: u32 },
+    /// Uses the General Radar ability on a value, without ending the turn.
+    Radar { value: u32 },
+    Save(String),
+    Load(String),
+    Undo,
+    Redo,
+}
+
+pub trait Player {
+    /// Chooses the position of the own cable which gets the info token at the start of the game.
+    fn choose_token(&mut self, view: &PlayerView) -> u32;
+    /// Chooses what to do on the player's turn.
+    fn choose_turn(&mut self, view: &PlayerView) -> Turn;
+    /// Whether the player decides at the terminal and can be asked again after an invalid move.
+    fn is_human(&self) -> bool {
+        false
+    }
+}
+
+/// Parses the seats of a game, one letter per player: h for a human, b for a bot and m for a Monte Carlo bot, e.g. "hbm".
+pub fn parse_seats(text: &str, players: u32) -> Result<Vec<Seat>, String> {
+    let seats = text.chars()
+        .map(|c| match c {
+            'h' => Ok(Seat::Human),
+            'b' => Ok(Seat::Bot),
+            'm' => Ok(Seat::MonteCarlo),
+            other => Err(format!("'{}' is not a seat, use h for a human, b for a bot or m for a Monte Carlo bot", other)),
+        })
+        .collect::<Result<Vec<Seat>, String>>()?;
+    if seats.len() != players as usize {
+        return Err(format!("Expected {} seats, found {}", players, seats.len()));
+    }
+    Ok(seats)
+}
+
+/// Creates the players of the seats. The bots are seeded from `seed`, so that the same game is played the same way.
+/// Monte Carlo bots search within `budget`. In hot-seat mode, the humans share the terminal and hand it over between their turns.
+pub fn init_players(seats: &[Seat], seed: u64, budget: Budget, hot_seat: bool) -> Vec<Box<dyn Player>> {
+    let terminal = hot_seat.then(|| Rc::new(Cell::new(None)));
+    seats.iter().enumerate()
+        .map(|(i, seat)| -> Box<dyn Player> {
+            match seat {
+                Seat::Human => Box::new(TerminalPlayer::new(terminal.clone())),
+                Seat::Bot => Box::new(HeuristicBot::new(seed.wrapping_add(i as u64))),
+                Seat::MonteCarlo => Box::new(MonteCarloBot::new(seed.wrapping_add(i as u64), budget)),
+            }
+        })
+        .collect()
+}
+
+/// Lets every player place their info token, before the first turn.
+pub fn place_tokens(state: &mut GameState, players: &mut Vec<Box<dyn Player>>) -> Result<(), String> {
+    for (i, player) in players.iter_mut().enumerate() {
+        let position = player.choose_token(&player_view(state, i as u32));
+        record_event(state, GameEvent::TokenPlaced { player: i as u32, position })?;
+    }
+    Ok(())
+}
+
+/// Plays a move of the current player. Returns whether a cut was successful, a solo cut and revealing red always are.
+pub fn play_move(state: &mut GameState, mv: Move) -> Result<bool, String> {
+    match mv {
+        Move::DualCut { teammate, position_self, position_teammate } => play_dual_cut(state, teammate, position_self, position_teammate),
+        Move::DoubleDetectorCut { teammate, position_self, positions_teammate } => play_double_detector_cut(state, teammate, position_self, positions_teammate),
+        Move::SoloCut { value } => play_solo_cut(state, value).map(|_| true),
+        Move::RevealRed => play_reveal_red(state).map(|_| true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_seats() {
+        assert_eq!(parse_seats("hbm", 3), Ok(vec![Seat::Human, Seat::Bot, Seat::MonteCarlo]));
+        assert!(parse_seats("hb", 3).is_err(), "Expected a missing seat to be rejected");
+        assert!(parse_seats("hx", 2).is_err(), "Expected an unknown seat to be rejected");
+    }
+}
@@ -0,0 +1,172 @@
+//! This module runs complete games with bots only, without any prompts. The results of many games show
+//! how hard a configuration is: how often the bomb is defused, why it explodes and how long the games take.
+//! The games are spread over several threads. Every game is seeded by its own number and the results are
+//! summarised in the order of the seeds, so the report does not depend on the number of threads.
+
+use std::ops::Range;
+use std::thread;
+
+use crate::elements::get_color;
+use crate::game::{GameMeta, GameOutcome, GameState, get_outcome};
+use crate::mission::Mission;
+use crate::player::{CableStatus, get_cables, get_status};
+use crate::montecarlo::Budget;
+use crate::seat::{Seat, Turn, init_players, place_tokens, play_move};
+use crate::view::player_view;
+
+/// The end of a simulated game.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GameResult {
+    /// `None` if the game did not end within the turn limit.
+    pub outcome: Option<GameOutcome>,
+    pub death_counter: u32,
+    pub turns: u32,
+}
+
+/// Summary of many simulated games.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SimulationReport {
+    pub games: u32,
+    pub defused: u32,
+    pub red_cable_cut: u32,
+    pub detonator_expired: u32,
+    /// Games which did not end within the turn limit.
+    pub unfinished: u32,
+    pub total_death_counter: u32,
+    pub total_turns: u32,
+}
+
+impl SimulationReport {
+    pub fn add(&mut self, result: &GameResult) {
+        self.games += 1;
+        match result.outcome {
+            Some(GameOutcome::Defused) => self.defused += 1,
+            Some(GameOutcome::RedCableCut) => self.red_cable_cut += 1,
+            Some(GameOutcome::DetonatorExpired) => self.detonator_expired += 1,
+            None => self.unfinished += 1,
+        }
+        self.total_death_counter += result.death_counter;
+        self.total_turns += result.turns;
+    }
+}
+
+pub fn win_rate(report: &SimulationReport) -> f64 {
+    ratio(report.defused, report.games)
+}
+
+pub fn average_death_counter(report: &SimulationReport) -> f64 {
+    ratio(report.total_death_counter, report.games)
+}
+
+pub fn average_turns(report: &SimulationReport) -> f64 {
+    ratio(report.total_turns, report.games)
+}
+
+fn ratio(part: u32, total: u32) -> f64 {
+    match total {
+        0 => 0.0,
+        _ => part as f64 / total as f64,
+    }
+}
+
+/// Plays one game with the given seed. Every seat has to be a bot.
+/// Monte Carlo bots only search for the iterations of `budget` without a time limit, so the game does not depend on the load of the machine.
+pub fn simulate_game(meta: &GameMeta, mission: &Option<Mission>, seats: &[Seat], seed: u64, budget: Budget) -> Result<GameResult, String> {
+    if seats.contains(&Seat::Human) {
+        return Err("Simulations can only be played by bots".to_string());
+    }
+    let mut state = GameState::deal(meta.clone(), mission.clone(), seed);
+    let budget = Budget { time: None, ..budget };
+    let mut players = init_players(seats, seed, budget, false);
+    place_tokens(&mut state, &mut players)?;
+
+    let max_turns = turns_left(&state);
+    let mut outcome;
+    loop {
+        outcome = get_outcome(&state.hands, &state.all_cables, state.death_counter);
+        if outcome.is_some() || state.turn > max_turns {
+            break;
+        }
+        let player = state.current_player;
+        let Turn::Play(mv) = players[player as usize].choose_turn(&player_view(&state, player)) else {
+            return Err(format!("Player {} did not make a move", player));
+        };
+        play_move(&mut state, mv)?;
+    }
+    Ok(GameResult { outcome, death_counter: state.death_counter, turns: state.turn - 1 })
+}
+
+/// Upper bound on the turns a game can still take. Every turn lowers the hidden cables plus the uncut cables
+/// which are not red plus the detonator: a successful cut cuts a cable which is not red, a failed cut ticks
+/// the detonator and revealing red cables turns hidden cables face up.
+fn turns_left(state: &GameState) -> u32 {
+    let mut left = state.death_counter;
+    for hand in &state.hands {
+        for (id, status) in get_cables(hand).iter().zip(get_status(hand)) {
+            if *status == CableStatus::Hidden {
+                left += 1;
+            }
+            if *status != CableStatus::Revealed && get_color(*id, &state.all_cables) != "red" {
+                left += 1;
+            }
+        }
+    }
+    left
+}
+
+/// Plays one game for every seed of the range on `threads` threads and summarises the results.
+pub fn run_simulation(meta: &GameMeta, mission: &Option<Mission>, seats: &[Seat], seeds: Range<u64>, threads: usize, budget: Budget) -> Result<SimulationReport, String> {
+    let seeds: Vec<u64> = seeds.collect();
+    let chunk_size = seeds.len().div_ceil(threads.max(1)).max(1);
+    let chunks: Vec<Result<Vec<GameResult>, String>> = thread::scope(|scope| {
+        let handles: Vec<_> = seeds.chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|seed| simulate_game(meta, mission, seats, *seed, budget)).collect()))
+            .collect();
+        handles.into_iter().map(|handle| handle.join().expect("Simulation thread panicked")).collect()
+    });
+    let mut report = SimulationReport::default();
+    for chunk in chunks {
+        for result in chunk? {
+            report.add(&result);
+        }
+    }
+    Ok(report)
+}
+
+/// Number of threads to simulate on: one per CPU core.
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_simulation() {
+        let meta = GameMeta::new(6, 1, 1, 0, 0, 3);
+        let seats = vec![Seat::Bot; 3];
+        let report = run_simulation(&meta, &None, &seats, 0..4, 1, Budget::default()).unwrap();
+        assert_eq!(report.games, 4, "Expected one game per seed");
+        assert_eq!(report.defused + report.red_cable_cut + report.detonator_expired + report.unfinished, 4, "Expected every game to be counted once");
+        assert_eq!(report.unfinished, 0, "Expected every game to end within the turn bound");
+        assert!((0.0..=1.0).contains(&win_rate(&report)), "Expected the win rate to be a share");
+        assert_eq!(run_simulation(&meta, &None, &seats, 0..4, 3, Budget::default()).unwrap(), report, "Expected the same results on any number of threads");
+    }
+
+    #[test]
+    fn test_turns_left() {
+        let mut state = GameState::deal(GameMeta::new(6, 1, 1, 0, 0, 2), None, 3);
+        let cables = state.all_cables.len() as u32;
+        assert_eq!(turns_left(&state), 2 * (cables - 1) + 1 + state.death_counter, "Expected every hidden cable and every cable which is not red to count");
+        let before = turns_left(&state);
+        state.death_counter -= 1;
+        assert_eq!(turns_left(&state), before - 1, "Expected a detonator tick to lower the bound");
+    }
+
+    #[test]
+    fn test_simulation_rejects_humans() {
+        let meta = GameMeta::new(6, 0, 0, 0, 0, 2);
+        assert!(simulate_game(&meta, &None, &[Seat::Human, Seat::Bot], 0, Budget::default()).is_err(), "Expected human seats to be rejected");
+    }
+}
@@ -1,218 +1,867 @@
 //! This module contains functions for the terminal interface of the game.
 //!
 
-use std::collections::HashMap;
-
+use std::cell::Cell;
+use std::fs;
 use std::io::{self, Write};
+use std::ops::{Range, RangeInclusive};
+use std::path::Path;
+use std::rc::Rc;
+use std::process;
 
 use crate::actions::*;
+use crate::advice::*;
+use crate::bot::fallback_move;
+use crate::campaign::*;
+use crate::command::*;
+use crate::character::*;
+use crate::deduction::*;
+use crate::difficulty::*;
 use crate::elements::*;
+use crate::events::*;
 use crate::game::*;
+use crate::mission::*;
+use crate::montecarlo::Budget;
+use crate::notation::*;
+use crate::options::*;
 use crate::player::*;
+use crate::save::*;
+use crate::seat::*;
+use crate::simulation::*;
+use crate::view::*;
 
-pub fn create_new_game() {
+/// Asks for the game configuration. If a mission is selected, only the number of players and the seats are asked.
+/// The next mission of the campaign is offered by default. Settings from the command line are not asked again.
+pub fn create_new_game(options: &GameOptions) -> Result<(GameMeta, Option<Mission>, Vec<Seat>), String> {
     println!("Welcome to Bomb Buster!");
     io::stdout().flush().unwrap();
 
-    let num_players = def_number_players();
-    let max_blue_cables = def_max_blue_cables();
-    let max_red_cables = def_max_red_cables();
-    let keep_red_cables = def_keep_red_cables(max_red_cables);
-    let max_yellow_cables = def_max_yellow_cables();
-    let keep_yellow_cables = def_keep_yellow_cables(max_yellow_cables);
+    let mission = match &options.mission {
+        Some(mission) => Some(mission.clone()),
+        None if has_cable_options(options) => None,
+        None => {
+            let campaign = match campaign_path().map(|path| load_campaign(&path)) {
+                Some(Ok(campaign)) => campaign,
+                Some(Err(e)) => {
+                    println!("{}", e);
+                    Campaign::default()
+                }
+                None => Campaign::default(),
+            };
+            def_mission(next_mission(&campaign))
+        }
+    };
+    if let (Some(mission), None) = (&mission, options_players(options)) {
+        let players: Vec<String> = mission_players(mission).iter().map(|p| p.to_string()).collect();
+        println!("Mission {} can be played with {} players.", mission.number, players.join(", "));
+    }
+    let num_players = options_players(options).unwrap_or_else(def_number_players);
+    let seats = options.seats.clone().unwrap_or_else(|| def_seats(num_players));
+    let casual = options.casual.unwrap_or_else(def_casual_mode);
+    let difficulty = options.difficulty.unwrap_or_else(def_difficulty);
+    if let Some(mission) = mission {
+        match mission_game_meta(&mission, num_players) {
+            Ok(mut game_meta) => {
+                println!("Starting mission {}: {} with {} players", mission.number, mission.name, num_players);
+                game_meta.casual = casual;
+                let mission = Some(mission);
+                if difficulty {
+                    show_difficulty(&game_meta, &mission);
+                }
+                return Ok((game_meta, mission, seats));
+            }
+            Err(e) if options.mission.is_some() => return Err(e),
+            Err(e) => println!("{} Setting up a custom game instead.", e),
+        }
+    }
 
+    let mut game_meta = def_custom_game_meta(num_players, options)?;
     println!(
         "Starting a new game with {} players, max blue cables: {}, max red cables: {}, keep red cables: {}, max yellow cables: {}, keep yellow cables: {}",
-        num_players, max_blue_cables, max_red_cables, keep_red_cables, max_yellow_cables, keep_yellow_cables
+        num_players, game_meta.blue_max, game_meta.red_show, game_meta.red_keep, game_meta.yellow_show, game_meta.yellow_keep
     );
+    game_meta.casual = casual;
+    if difficulty {
+        show_difficulty(&game_meta, &None);
+    }
+    Ok((game_meta, None, seats))
 }
 
-pub fn def_number_players() -> u32 {
+/// Estimates the difficulty of the configuration with bot games and shows the rating.
+pub fn show_difficulty(meta: &GameMeta, mission: &Option<Mission>) {
+    println!("Estimating the difficulty with {} bot games...", DIFFICULTY_GAMES);
+    match estimate_difficulty(meta, mission, DIFFICULTY_GAMES) {
+        Ok((difficulty, report)) => println!(
+            "Difficulty: {:?}, {} ({:.0}% of the bot games defused)",
+            difficulty, describe_difficulty(difficulty), win_rate(&report) * 100.0
+        ),
+        Err(e) => println!("Could not estimate the difficulty: {}", e),
+    }
+}
+
+/// Asks whether the difficulty should be estimated with bot games. Pressing enter skips the estimate.
+pub fn def_difficulty() -> bool {
+    loop {
+        print!("Estimate the difficulty with {} bot games first? (y/n, press enter for no): ", DIFFICULTY_GAMES);
+        io::stdout().flush().unwrap();
+
+        match read_line_or_quit().trim() {
+            "y" | "yes" => return true,
+            "" | "n" | "no" => return false,
+            _ => println!("Please enter y or n."),
+        }
+    }
+}
+
+/// Asks who takes each seat. Pressing enter seats a human at every place.
+pub fn def_seats(players: u32) -> Vec<Seat> {
     loop {
-        print!("Enter the number of players (2-4): ");
+        print!(
+            "Enter h for a human, b for a bot or m for a Monte Carlo bot per player, e.g. 'h{}' (press enter for humans only): ",
+            "m".repeat(players as usize - 1)
+        );
         io::stdout().flush().unwrap();
 
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => match input.trim().parse::<u32>() {
-                Ok(num) if num >= 2 && num <= 4 => return num,
-                _ => println!("Please enter a valid number between 2 and 4."),
+        match read_line_or_quit().trim() {
+            "" => return vec![Seat::Human; players as usize],
+            text => match parse_seats(text, players) {
+                Ok(seats) => return seats,
+                Err(e) => println!("{}", e),
             },
-            Err(_) => println!("Failed to read input. Please try again."),
         }
     }
 }
 
-pub fn def_max_blue_cables() -> u32 {
+/// Asks whether the game is casual. Casual games allow undoing actions.
+pub fn def_casual_mode() -> bool {
     loop {
-        print!("Enter the maximum value for blue cables (8-12): ");
+        print!("Play a casual game with undo and redo? (y/n): ");
         io::stdout().flush().unwrap();
 
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => match input.trim().parse::<u32>() {
-                Ok(num) if num >= 8 && num <= 12 => return num,
-                _ => println!("Please enter a valid number between 8 and 12."),
+        match read_line_or_quit().trim() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            _ => println!("Please enter y or n."),
+        }
+    }
+}
+
+/// Asks for a mission. Pressing enter selects `default_mission`, or a custom game if there is none.
+pub fn def_mission(default_mission: Option<u32>) -> Option<Mission> {
+    let default_text = match default_mission {
+        Some(number) => format!("press enter for mission {}, 'custom' for a custom game", number),
+        None => "press enter for a custom game".to_string(),
+    };
+    loop {
+        print!("Enter a mission number, the path of a mission file or 'list' to show all missions ({}): ", default_text);
+        io::stdout().flush().unwrap();
+
+        match read_line_or_quit().trim() {
+            "" => return default_mission.and_then(get_catalogue_mission),
+            "custom" => return None,
+            "list" => show_mission_catalogue(),
+            text => match text.parse::<u32>() {
+                Ok(number) => match get_catalogue_mission(number) {
+                    Some(mission) => return Some(mission),
+                    None => println!("There is no mission {}. Enter 'list' to show all missions.", number),
+                },
+                Err(_) => match load_mission(Path::new(text)) {
+                    Ok(mission) => return Some(mission),
+                    Err(e) => println!("{}", e),
+                },
             },
-            Err(_) => println!("Failed to read input. Please try again."),
         }
     }
 }
 
-pub fn def_max_red_cables() -> u32 {
+pub fn def_number_players() -> u32 {
+    def_number(&format!("the number of players ({}-{})", MIN_PLAYERS, MAX_PLAYERS), MIN_PLAYERS..=MAX_PLAYERS, None)
+}
+
+/// Asks for the cables of a custom game until the configuration is valid. Cables from the command line are not asked,
+/// if all of them are given an invalid configuration is an error. Pressing enter takes the recommended value.
+pub fn def_custom_game_meta(players: u32, options: &GameOptions) -> Result<GameMeta, String> {
+    let recommended = GameMeta::recommended(players)?;
     loop {
-        print!("Enter the number of red cables to show (0-3): ");
+        let blue_max = options.blue_max
+            .unwrap_or_else(|| def_number(&format!("the maximum value for blue cables (1-{})", MAX_BLUE), 1..=MAX_BLUE, Some(recommended.blue_max)));
+        let red_show = options.red_show
+            .unwrap_or_else(|| def_number(&format!("the number of red cables to show (0-{})", MAX_RED), 0..=MAX_RED, Some(recommended.red_show)));
+        let red_keep = options.red_keep.unwrap_or_else(|| def_keep(red_show, "red", recommended.red_keep));
+        let yellow_show = options.yellow_show
+            .unwrap_or_else(|| def_number(&format!("the number of yellow cables to show (0-{})", MAX_YELLOW), 0..=MAX_YELLOW, Some(recommended.yellow_show)));
+        let yellow_keep = options.yellow_keep.unwrap_or_else(|| def_keep(yellow_show, "yellow", recommended.yellow_keep));
+        match GameMeta::try_new(blue_max, red_show, red_keep, yellow_show, yellow_keep, players) {
+            Ok(meta) => return Ok(meta),
+            Err(e) if [options.blue_max, options.red_show, options.red_keep, options.yellow_show, options.yellow_keep].iter().all(|c| c.is_some()) => return Err(e),
+            Err(e) => println!("{}. Please try again.", e),
+        }
+    }
+}
+
+/// Asks how many of the shown cables of a colour are kept. Nothing to ask if none or one is shown.
+fn def_keep(show: u32, colour: &str, recommended: u32) -> u32 {
+    match show {
+        0 | 1 => show,
+        _ => def_number(&format!("the number of {} cables to keep (0-{})", colour, show), 0..=show, Some(recommended.min(show))),
+    }
+}
+
+/// Asks for a number in a range. With a default, pressing enter selects it.
+fn def_number(description: &str, range: RangeInclusive<u32>, default: Option<u32>) -> u32 {
+    loop {
+        match default {
+            Some(default) => print!("Enter {} (press enter for {}): ", description, default),
+            None => print!("Enter {}: ", description),
+        }
         io::stdout().flush().unwrap();
 
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => match input.trim().parse::<u32>() {
-                Ok(num) if num >= 0 && num <= 3 => return num,
-                _ => println!("Please enter a valid number between 0 and 3."),
+        match (read_line_or_quit().trim(), default) {
+            ("", Some(default)) => return default,
+            (text, _) => match text.parse::<u32>() {
+                Ok(num) if range.contains(&num) => return num,
+                _ => println!("Please enter a valid number between {} and {}.", range.start(), range.end()),
             },
-            Err(_) => println!("Failed to read input. Please try again."),
         }
     }
 }
 
-pub fn def_keep_red_cables(max_red_cables: u32) -> u32 {
-    match max_red_cables {
-        0 => 0,
-        1 => 1,
-        _ => loop {
-            print!(
-                "Enter the number of red cables to keep (0-{}): ",
-                max_red_cables
+/// A human player at the terminal. Asks for every decision and offers advice and range hints.
+pub struct TerminalPlayer {
+    assist: bool,
+    /// In hot-seat mode, the player who has the terminal, shared by all humans.
+    terminal: Option<Rc<Cell<Option<u32>>>>,
+}
+
+impl TerminalPlayer {
+    pub fn new(terminal: Option<Rc<Cell<Option<u32>>>>) -> Self {
+        TerminalPlayer { assist: false, terminal }
+    }
+
+    /// In hot-seat mode, clears the screen and waits until the player has the terminal,
+    /// so that the previous player never sees the cables of the next one.
+    fn take_terminal(&self, player: u32) {
+        let Some(terminal) = &self.terminal else {
+            return;
+        };
+        if terminal.get() == Some(player) {
+            return;
+        }
+        clear_screen();
+        print!("Pass the terminal to player {}, press enter when ready: ", player);
+        io::stdout().flush().unwrap();
+        read_line_or_quit();
+        terminal.set(Some(player));
+    }
+}
+
+impl Player for TerminalPlayer {
+    fn choose_token(&mut self, view: &PlayerView) -> u32 {
+        self.take_terminal(view.player);
+        println!("Player {}, place your info token.", view.player);
+        show_hands(view, false);
+        def_position(&view.racks[view.player as usize], "the cable for your info token")
+    }
+
+    fn is_human(&self) -> bool {
+        true
+    }
+
+    fn choose_turn(&mut self, view: &PlayerView) -> Turn {
+        self.take_terminal(view.player);
+        println!("Turn {}, player {}. Detonator: {}", view.turn, view.player, view.death_counter);
+        show_cable_info(view);
+        show_characters(&view.characters);
+        show_hands(view, self.assist);
+        loop {
+            match def_command() {
+                Command::Dual { teammate, position_teammate, position_self } => {
+                    return Turn::Play(Move::DualCut { teammate, position_self, position_teammate });
+                }
+                Command::Double { teammate, positions_teammate, position_self } => {
+                    return Turn::Play(Move::DoubleDetectorCut { teammate, position_self, positions_teammate });
+                }
+                Command::Solo { value } => return Turn::Play(Move::SoloCut { value }),
+                Command::RevealRed => return Turn::Play(Move::RevealRed),
+                Command::Use { equipment, position } => return Turn::Use { equipment, position },
+                Command::Rewind => return Turn::Rewind,
+                Command::PostIt { position } => return Turn::PostIt { position },
+                Command::Radar { value } => return Turn::Radar { value },
+                Command::Save(path) => return Turn::Save(path),
+                Command::Load(path) => return Turn::Load(path),
+                Command::Undo => return Turn::Undo,
+                Command::Redo => return Turn::Redo,
+                Command::View => {
+                    show_cable_info(view);
+                    show_characters(&view.characters);
+                    show_hands(view, self.assist);
+                }
+                Command::Help => println!("{}", COMMAND_HELP),
+                Command::Advise => show_advice(view),
+                Command::Assist => {
+                    self.assist = !self.assist;
+                    println!("Range hints are {}.", if self.assist { "on" } else { "off" });
+                    show_hands(view, self.assist);
+                }
+            }
+        }
+    }
+}
+
+/// Plays the game turn by turn until the bomb is defused or explodes. Each player decides on their own turn.
+/// A human is asked again after an invalid move, a bot plays its fallback move instead. Fails if that is invalid too.
+pub fn play_game(state: &mut GameState, players: &mut [Box<dyn Player>]) -> Result<GameOutcome, String> {
+    loop {
+        if let Some(outcome) = get_outcome(&state.hands, &state.all_cables, state.death_counter) {
+            return Ok(outcome);
+        }
+        let player = state.current_player;
+        // Humans see the turn with their own rack once they have the terminal, the others only what is public.
+        if !players[player as usize].is_human() {
+            println!("Turn {}, player {}. Detonator: {}", state.turn, player, state.death_counter);
+            let public = public_view(state);
+            if !public.red_values.is_empty() || !public.yellow_values.is_empty() {
+                show_cable_info(&public);
+            }
+        }
+        let view = player_view(state, player);
+
+        match players[player as usize].choose_turn(&view) {
+            Turn::Play(mv) => match play_move(state, mv) {
+                Ok(success) => show_move_result(player, mv, success, state),
+                Err(e) if players[player as usize].is_human() => println!("{}", e),
+                Err(e) => {
+                    let fallback = fallback_move(&view);
+                    println!("Player {}'s move was rejected ({}), playing {:?} instead.", player, e, fallback);
+                    let success = play_move(state, fallback).map_err(|e| format!("Player {} cannot make a move: {}", player, e))?;
+                    show_move_result(player, fallback, success, state);
+                }
+            },
+            Turn::Use { equipment, position } => match record_event(state, GameEvent::LabelPlaced { player, position, kind: equipment_label(equipment) }) {
+                Ok(()) => println!("Player {} placed a {:?} label on cables #{} and #{}.", player, equipment, position, position + 1),
+                Err(e) => println!("{}", e),
+            },
+            Turn::Rewind => match record_event(state, GameEvent::Rewound { player }) {
+                Ok(()) => println!("Player {} rewinds the detonator to {}.", player, state.death_counter),
+                Err(e) => println!("{}", e),
+            },
+            Turn::PostIt { position } => match record_event(state, GameEvent::PostIt { player, position }) {
+                Ok(()) => println!("Player {} places a post-it on own cable #{}.", player, position),
+                Err(e) => println!("{}", e),
+            },
+            Turn::Radar { value } => match record_event(state, GameEvent::GeneralRadar { player, value }) {
+                Ok(()) => show_radar_answer(player, value, &radar_answer(value, &state.hands, &state.all_cables)),
+                Err(e) => println!("{}", e),
+            },
+            Turn::Save(path) => match save_game(state, Path::new(&path)) {
+                Ok(()) => println!("Game saved to {}.", path),
+                Err(e) => println!("{}", e),
+            },
+            Turn::Load(path) => match load_game(Path::new(&path), players.len() as u32) {
+                Ok(loaded) => {
+                    *state = loaded;
+                    println!("Game loaded from {}.", path);
+                }
+                Err(e) => println!("{}", e),
+            },
+            Turn::Undo => match undo(state) {
+                Ok(()) => println!("Last action undone."),
+                Err(e) => println!("{}", e),
+            },
+            Turn::Redo => match redo(state) {
+                Ok(()) => println!("Action redone."),
+                Err(e) => println!("{}", e),
+            },
+        }
+    }
+}
+
+fn show_move_result(player: u32, mv: Move, success: bool, state: &GameState) {
+    match mv {
+        Move::DualCut { teammate, position_self, position_teammate } => {
+            println!("Player {} cuts with own cable #{} on player {}'s cable #{}.", player, position_self, teammate, position_teammate);
+            show_dual_cut_result(success, teammate, position_teammate, state);
+        }
+        Move::DoubleDetectorCut { teammate, position_self, positions_teammate } => {
+            println!(
+                "Player {} cuts with own cable #{} on player {}'s cables #{} and #{} with the Double Detector.",
+                player, position_self, teammate, positions_teammate.0, positions_teammate.1
             );
-            io::stdout().flush().unwrap();
-
-            let mut input = String::new();
-            match io::stdin().read_line(&mut input) {
-                Ok(_) => match input.trim().parse::<u32>() {
-                    Ok(num) if num <= max_red_cables => return num,
-                    _ => println!(
-                        "Please enter a valid number between 0 and {}.",
-                        max_red_cables
-                    ),
-                },
-                Err(_) => println!("Failed to read input. Please try again."),
+            match success {
+                true => println!("Double Detector cut successful! Both cables revealed."),
+                false => println!("Double Detector cut failed! Teammate's cable #{} is now a clue and the detonator ticks.", positions_teammate.0),
             }
-        },
+        }
+        Move::SoloCut { value } => println!("Player {} solo cuts all cables with value {}.", player, value as f32 / 10.0),
+        Move::RevealRed => println!("Player {} reveals their remaining red cables.", player),
+    }
+}
+
+/// Shows which players hold a not yet revealed cable of the value asked with the General Radar.
+fn show_radar_answer(player: u32, value: u32, answer: &[bool]) {
+    println!("Player {} uses the General Radar on value {}:", player, value as f32 / 10.0);
+    for (i, holds) in answer.iter().enumerate() {
+        println!("Player {}: {}", i, if *holds { "yes" } else { "no" });
     }
 }
 
-pub fn def_max_yellow_cables() -> u32 {
+fn show_dual_cut_result(success: bool, teammate: u32, position_teammate: u32, state: &GameState) {
+    let cable_id = get_cables(&state.hands[teammate as usize])[position_teammate as usize];
+    println!("Value teammate: {}", get_value(cable_id, &state.all_cables) as f32 / 10.0);
+    if success {
+        println!("Duo cut successful! Both cables revealed.");
+    } else if get_color(cable_id, &state.all_cables) == "red" {
+        println!("Duo cut hit a red cable!");
+    } else {
+        println!("Duo cut failed! Teammate's cable is now a clue and the detonator ticks.");
+    }
+}
+
+/// Shows all events of the game after the deal, e.g. for a post-mortem of a lost game.
+pub fn show_log(log: &[GameEvent]) {
+    println!("Game log:");
+    for (i, event) in log.iter().enumerate() {
+        let description = match event {
+            GameEvent::Deal { meta, seed, .. } => format!("Deal for {} players, seed {}", meta.players, seed),
+            GameEvent::TokenPlaced { player, position } => format!("Player {} placed an info token on cable {}", player, position),
+            GameEvent::DualCut { player, teammate, position_self, position_teammate } => format!(
+                "Player {} dual cut with own cable {} on player {}'s cable {}", player, position_self, teammate, position_teammate
+            ),
+            GameEvent::DoubleDetectorCut { player, teammate, position_self, positions_teammate } => format!(
+                "Player {} double detector cut with own cable {} on player {}'s cables {} and {}",
+                player, position_self, teammate, positions_teammate.0, positions_teammate.1
+            ),
+            GameEvent::SoloCut { player, value } => format!("Player {} solo cut all cables with value {}", player, *value as f32 / 10.0),
+            GameEvent::LabelPlaced { player, position, kind } => format!("Player {} placed a {:?} label at position {}", player, kind, position),
+            GameEvent::Rewound { player } => format!("Player {} rewound the detonator", player),
+            GameEvent::PostIt { player, position } => format!("Player {} placed a post-it on cable {}", player, position),
+            GameEvent::GeneralRadar { player, value } => format!("Player {} used the general radar on value {}", player, *value as f32 / 10.0),
+            GameEvent::RedRevealed { player } => format!("Player {} revealed their red cables", player),
+            GameEvent::DetonatorTick => "The detonator ticked".to_string(),
+            GameEvent::TurnEnded => "Turn ended".to_string(),
+        };
+        println!("{:>4}. {}", i, description);
+    }
+}
+
+/// Asks for a command until the input can be parsed.
+pub fn def_command() -> Command {
     loop {
-        print!("Enter the number of yellow cables to show (0, 2 or 3): ");
+        print!("Enter a command ('help' lists all commands): ");
         io::stdout().flush().unwrap();
 
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => match input.trim().parse::<u32>() {
-                Ok(num) if num == 0 || num == 2 || num == 3 => return num,
-                _ => println!("Please enter 0, 2 or 3."),
-            },
-            Err(_) => println!("Failed to read input. Please try again."),
+        match parse_command(&read_line_or_quit()) {
+            Ok(command) => return command,
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+/// Clears the screen and its scrollback.
+pub fn clear_screen() {
+    print!("\x1B[2J\x1B[3J\x1B[H");
+    io::stdout().flush().unwrap();
+}
+
+/// Asks whether several humans share the terminal. Only asked with more than one human.
+pub fn def_hot_seat(seats: &[Seat]) -> bool {
+    if seats.iter().filter(|seat| **seat == Seat::Human).count() < 2 {
+        return false;
+    }
+    loop {
+        print!("Do the players share this terminal? The screen is cleared between turns (y/n): ");
+        io::stdout().flush().unwrap();
+
+        match read_line_or_quit().trim() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            _ => println!("Please enter y or n."),
         }
     }
 }
 
-pub fn def_keep_yellow_cables(max_yellow_cables: u32) -> u32 {
-    if max_yellow_cables == 0 || max_yellow_cables == 2 {
-        return max_yellow_cables;
+/// Reads a line of input. Leaves the game if the input is closed, e.g. by Ctrl-D.
+pub fn read_line_or_quit() -> String {
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => {
+            println!("\nInput closed, leaving the game.");
+            process::exit(0);
+        }
+        Ok(_) => input,
     }
-    match max_yellow_cables {
-        0 => 0,
-        _ => loop {
-            print!("Enter the number of yellow cables to keep (2 or 3): " );
-            io::stdout().flush().unwrap();
+}
 
-            let mut input = String::new();
-            match io::stdin().read_line(&mut input) {
-                Ok(_) => match input.trim().parse::<u32>() {
-                    Ok(num) if num == 2 || num == 3 => return num,
-                    _ => println!("Please enter 2 or 3."),
-                },
-                Err(_) => println!("Failed to read input. Please try again."),
-            }
-        },
+/// Lists the safest dual cuts for the viewing player.
+pub fn show_advice(view: &PlayerView) {
+    let advice = cut_advice(view, ADVICE_SAMPLES, &mut rand::rng());
+    if advice.is_empty() {
+        println!("No advice available.");
+        return;
+    }
+    println!("Safest dual cuts:");
+    for a in advice.iter().take(5) {
+        println!(
+            "Your {} (#{}) on player {}'s #{}: success {:.0}%, red {:.0}%",
+            a.value as f32 / 10.0,
+            a.position_self,
+            a.teammate,
+            a.position,
+            a.success * 100.0,
+            a.red * 100.0
+        );
+    }
+}
+
+/// Asks for the position of a cable in a rack which has not been cut yet.
+pub fn def_position(rack: &RackView, description: &str) -> u32 {
+    let status: Vec<CableStatus> = rack.slots.iter().map(|slot| slot.status).collect();
+    loop {
+        print!("Enter the position of {} (0-{}): ", description, status.len() - 1);
+        io::stdout().flush().unwrap();
+
+        match read_line_or_quit().trim().parse::<usize>() {
+            Ok(num) if num < status.len() && status[num] != CableStatus::Revealed => return num as u32,
+            _ => println!("Please enter the position of a cable which has not been cut yet."),
+        }
+    }
+}
+
+/// Records the result of a built-in mission in the campaign file.
+pub fn record_campaign(mission_number: u32, outcome: GameOutcome, death_counter: u32) {
+    let Some(path) = campaign_path() else {
+        println!("No data directory found, the campaign progress is not saved.");
+        return;
+    };
+    let result = load_campaign(&path).and_then(|mut campaign| {
+        record_attempt(&mut campaign, mission_number, outcome, death_counter, &today());
+        save_campaign(&campaign, &path)
+    });
+    match result {
+        Ok(()) => println!("Campaign progress saved."),
+        Err(e) => println!("{}", e),
     }
 }
 
-pub fn show_cable_info(all_cables: &HashMap<u32, u32>, game_meta: &GameMeta) {
-    let (red_values, yellow_values) = get_yel_red_info(&all_cables);
+/// Offers to export the record of a finished game to a file.
+pub fn export_game_record(state: &GameState, outcome: GameOutcome) {
+    print!("Enter a file to save the game record, or press enter to skip: ");
+    io::stdout().flush().unwrap();
+
+    let input = read_line_or_quit();
+    if input.trim().is_empty() {
+        return;
+    }
+    let text = format_record(&record_from_state(state), Some(outcome));
+    match fs::write(input.trim(), text) {
+        Ok(()) => println!("Game record saved to {}.", input.trim()),
+        Err(e) => println!("Could not write game record {}: {}", input.trim(), e),
+    }
+}
+
+/// Replays a game record action by action. Each step waits for enter.
+pub fn replay_game_record(path: &str) {
+    let record = match fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|text| parse_record(&text)) {
+        Ok(record) => record,
+        Err(e) => {
+            println!("Could not load game record {}: {}", path, e);
+            return;
+        }
+    };
+    if let Some(mission) = &record.mission {
+        show_mission(mission);
+    }
+    let result = replay_record(&record, |action, state| {
+        println!("{}. {}", action.turn, format_record_action(action));
+        println!("Detonator: {}", state.death_counter);
+        if let Some(player) = acting_player(&action.event) {
+            show_hands(&player_view(state, player), false);
+        }
+        print!("Press enter to continue: ");
+        io::stdout().flush().unwrap();
+        read_line_or_quit();
+    });
+    match result {
+        Ok((state, Some(outcome))) => show_outcome(outcome, state.death_counter),
+        Ok(_) => println!("The record ends before the end of the game."),
+        Err(e) => println!("{}", e),
+    }
+}
+
+pub fn show_outcome(outcome: GameOutcome, death_counter: u32) {
+    match outcome {
+        GameOutcome::Defused => println!("The bomb is defused! Remaining detonator: {}", death_counter),
+        GameOutcome::RedCableCut => println!("A red cable was cut. The bomb exploded!"),
+        GameOutcome::DetonatorExpired => println!("The detonator expired. The bomb exploded!"),
+    }
+}
+
+/// Shows which of the shown red and yellow cables are in play, as far as the view tells.
+pub fn show_cable_info(view: &PlayerView) {
+    let (red, yellow) = cables_in_play(view, &possible_values(view));
     println!("Cable Information:");
-    println!(
-        "Red cable values: {:?}, in game: {:?}",
-        red_values, game_meta.red_keep
-    );
-    println!(
-        "Yellow cables: {:?}, in game: {:?}",
-        yellow_values, game_meta.yellow_keep
-    );
+    println!("Red cable values: {}, in game: {}", format_in_play(&red), view.meta.red_keep);
+    println!("Yellow cables: {}, in game: {}", format_in_play(&yellow), view.meta.yellow_keep);
 }
 
-/// Shows all hands from the perspective of the current player.
-pub fn show_hands(player_number: u32, hands: &Vec<Hand>, all_cables: &HashMap<u32, u32>) {
-    let hand_player: Hand = hands[player_number as usize].clone();
+/// Formats the possible values of a cable as a range, e.g. `[5–7]`, or `[5]` if there is only one.
+fn format_range(possible: &[u32]) -> String {
+    match value_range(possible) {
+        Some((low, high)) if low == high => format!("[{}]", low as f32 / 10.0),
+        Some((low, high)) => format!("[{}–{}]", low as f32 / 10.0, high as f32 / 10.0),
+        None => "[?]".to_string(),
+    }
+}
 
+/// Formats shown red or yellow values with whether they are in play, e.g. `2.5 (in play), 7.5 (maybe)`.
+fn format_in_play(values: &[(u32, InPlay)]) -> String {
+    if values.is_empty() {
+        return "none".to_string();
+    }
+    values.iter()
+        .map(|(value, in_play)| {
+            let state = match in_play {
+                InPlay::Yes => "in play",
+                InPlay::No => "out",
+                InPlay::Maybe => "maybe",
+            };
+            format!("{} ({})", *value as f32 / 10.0, state)
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Meta, mission, seats, seeds, threads and bot budget of a simulation.
+type SimulationSetup = (GameMeta, Option<Mission>, Vec<Seat>, Range<u64>, usize, Budget);
+
+/// Runs bot games without prompts, e.g. `--simulate 100 --mission 3 --players 4 --seed 0 --bot m --threads 8 --mc-iterations 50`.
+/// Without a mission the default setup is simulated, with heuristic bots, seeds from 0 and one thread per CPU core.
+pub fn run_simulation_command(args: &[String]) {
+    let (meta, mission, seats, seeds, threads, budget) = match simulation_setup(args) {
+        Ok(setup) => setup,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    match run_simulation(&meta, &mission, &seats, seeds, threads, budget) {
+        Ok(report) => show_simulation_report(&report),
+        Err(e) => println!("{}", e),
+    }
+}
+
+/// Parses the arguments after `--simulate`: the number of games, `--bot` and `--threads`, and the game options of a new game.
+/// Cable options which are not given are taken from the recommended setup.
+fn simulation_setup(args: &[String]) -> Result<SimulationSetup, String> {
+    let (games, args) = args.split_first().ok_or("Please give the number of games after --simulate.")?;
+    let games = games.parse::<u64>().map_err(|_| format!("'{}' is not a number of games", games))?;
+    let mut bot = None;
+    let mut threads = default_threads();
+    let mut game_args = Vec::new();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--bot" => bot = Some(args.next().ok_or("Please give a value after --bot")?),
+            "--threads" => {
+                let value = args.next().ok_or("Please give a value after --threads")?;
+                threads = value.parse::<usize>().map_err(|_| format!("'{}' is not a number for --threads", value))?;
+            }
+            _ => game_args.push(flag.clone()),
+        }
+    }
+    let options = parse_game_options(&game_args)?;
+    if options.casual.is_some() || options.hot_seat.is_some() || options.difficulty.is_some() || options.mc_time.is_some() {
+        return Err("--casual, --hot-seat, --difficulty and --mc-time have no effect on a simulation".to_string());
+    }
+    let players = options_players(&options).unwrap_or(3);
+    let meta = match &options.mission {
+        Some(mission) => mission_game_meta(mission, players)?,
+        None => {
+            let recommended = GameMeta::recommended(players)?;
+            GameMeta::try_new(
+                options.blue_max.unwrap_or(recommended.blue_max),
+                options.red_show.unwrap_or(recommended.red_show),
+                options.red_keep.unwrap_or(recommended.red_keep),
+                options.yellow_show.unwrap_or(recommended.yellow_show),
+                options.yellow_keep.unwrap_or(recommended.yellow_keep),
+                players,
+            )?
+        }
+    };
+    let seats = match (options.seats.clone(), bot) {
+        (Some(_), Some(_)) => return Err("Please give either --seats or --bot".to_string()),
+        (Some(seats), None) => seats,
+        (None, bot) => parse_seats(&bot.map(|bot| bot.as_str()).unwrap_or("b").repeat(players as usize), players)?,
+    };
+    let first_seed = options.seed.unwrap_or(0);
+    let last_seed = first_seed.checked_add(games).ok_or(format!("{} games from seed {} run out of seeds", games, first_seed))?;
+    let budget = Budget { time: None, ..options_budget(&options) };
+    Ok((meta, options.mission, seats, first_seed..last_seed, threads, budget))
+}
+
+pub fn show_simulation_report(report: &SimulationReport) {
+    println!("Games: {}", report.games);
+    println!("Defused: {} ({:.1}%)", report.defused, win_rate(report) * 100.0);
+    println!("Red cable cut: {}", report.red_cable_cut);
+    println!("Detonator expired: {}", report.detonator_expired);
+    if report.unfinished > 0 {
+        println!("Unfinished: {}", report.unfinished);
+    }
+    println!("Average remaining detonator: {:.2}", average_death_counter(report));
+    println!("Average turns: {:.1}", average_turns(report));
+}
+
+/// Lists the built-in missions with their cable setup.
+pub fn show_mission_catalogue() {
+    println!("Missions:");
+    for mission in mission_catalogue() {
+        println!(
+            "{:>3}. {:<22} blue 1-{}, red {} of {}, yellow {} of {}",
+            mission.number, mission.name, mission.blue_max, mission.red_keep, mission.red_show, mission.yellow_keep, mission.yellow_show
+        );
+    }
+}
+
+pub fn show_mission(mission: &Mission) {
+    println!("Mission {}: {}", mission.number, mission.name);
+    if !mission.intro.is_empty() {
+        println!("{}", mission.intro);
+    }
+    if !mission.equipment.is_empty() {
+        println!("Equipment: {:?}", mission.equipment);
+    }
+    for rule in &mission.special_rules {
+        println!("Special rule: {}", rule);
+    }
+}
+
+/// Shows all hands from the perspective of the viewing player.
+/// Shows the own rack and the teammates' racks. With `assist`, every hidden cable of a teammate
+/// shows the range of values it can still have, e.g. `[5–7]`.
+pub fn show_hands(view: &PlayerView, assist: bool) {
+    let possible = match assist {
+        true => Some(possible_values(view)),
+        false => None,
+    };
     // Display the current player's hand
-    println!("Player {}'s hand:", player_number);
-    for n in 0..get_cables(&hand_player).len() {
+    let own_rack = &view.racks[view.player as usize];
+    println!("Player {}'s hand (your own):", view.player);
+    for (n, slot) in own_rack.slots.iter().enumerate() {
+        let value = slot.value.expect("Own cables are visible");
+        let state = match slot.status {
+            CableStatus::Hidden => "not cut",
+            CableStatus::Clue => "not cut, info token",
+            CableStatus::Revealed => "cut",
+        };
         println!(
-            "Value: {}, Color: {}, Status: {:?}",
-            get_value(get_cables(&hand_player)[n], all_cables) as f32 / 10.0,
-            get_color(get_cables(&hand_player)[n], all_cables),
-            get_cables(&hand_player)[n]
+            "#{:<2} Value: {:<4} Color: {:<7} {}",
+            n,
+            value as f32 / 10.0,
+            get_value_color(value),
+            state
         );
+        show_label(own_rack, n);
+    }
+    if !own_rack.claims.is_empty() {
+        let claims: Vec<String> = own_rack.claims.iter().map(|value| (*value as f32 / 10.0).to_string()).collect();
+        println!("You claimed: {}", claims.join(", "));
     }
 
     // Display the hands of other players
-    for (i, hand) in hands.iter().enumerate() {
-        if i as u32 != player_number {
+    for (i, rack) in view.racks.iter().enumerate() {
+        if i as u32 != view.player {
             println!("Player {}'s hand:", i);
-            for n in 0..get_cables(&hand).len() {
-                let status = match get_status(hand)[n] {
+            for (n, slot) in rack.slots.iter().enumerate() {
+                let status = match slot.status {
                     CableStatus::Hidden => "Hidden",
                     CableStatus::Clue => "Clue",
                     CableStatus::Revealed => "Revealed",
                 };
-                let color = match status {
-                    "Hidden" => "Hidden".to_string(),
-                    _ => get_color(get_cables(&hand)[n], all_cables),
+                let color = match slot.value {
+                    None => "Hidden".to_string(),
+                    Some(value) => get_value_color(value),
+                };
+                let value = match slot.value {
+                    None => "Hidden".to_string(),
+                    Some(value) => (value as f32 / 10.0).to_string(),
                 };
-                let value = match status {
-                    "Hidden" => "Hidden".to_string(),
-                    _ => (get_value(get_cables(&hand)[n], all_cables) as f32 / 10.0).to_string(),
+                let range = match (&possible, slot.value) {
+                    (Some(possible), None) => format!(" {}", format_range(&possible[i][n])),
+                    _ => String::new(),
                 };
-                println!("Value: {}, Color: {}, Status: {:?}", value, color, status);
+                println!("Value: {}, Color: {}, Status: {:?}{}", value, color, status, range);
+                show_label(rack, n);
+            }
+            if !rack.claims.is_empty() {
+                let claims: Vec<String> = rack.claims.iter().map(|value| (*value as f32 / 10.0).to_string()).collect();
+                println!("Claimed values: {}", claims.join(", "));
             }
         }
     }
 }
 
+/// Shows the character of every player and whether their ability is still available.
+pub fn show_characters(characters: &[Character]) {
+    println!("Characters:");
+    for (i, character) in characters.iter().enumerate() {
+        let availability = match is_ability_used(character) {
+            true => "used",
+            false => "available",
+        };
+        println!(
+            "Player {}: {}, Ability: {} ({})",
+            i,
+            get_name(character),
+            ability_name(get_ability(character)),
+            availability
+        );
+    }
+}
+
+/// Prints the label between the cable at `position` and its right neighbour, if there is one.
+fn show_label(rack: &RackView, position: usize) {
+    match rack.labels.iter().find(|label| label.position == position).map(|label| label.kind) {
+        Some(LabelKind::Equal) => println!("  = (same value as the next cable)"),
+        Some(LabelKind::Different) => println!("  ≠ (different value from the next cable)"),
+        None => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::new_game;
 
     #[test]
     fn test_show_hands() {
-        let all_cables = init_all_cables(10, 3, 2);
-        let in_game_cables = init_cables_in_game(&all_cables, 2, 1);
-        let cable_distributions: Vec<Vec<u32>> = init_cable_distribution(&in_game_cables, 3);
-        assert!(!cable_distributions.is_empty(), "Hands should not be empty");
-
-        let mut sorted_hands = cable_distributions.clone();
-        sort_cable_distribution(&mut sorted_hands, &all_cables);
-        let initialized_hands = init_hands(sorted_hands);
-
-        assert_eq!(
-            initialized_hands.len(),
-            cable_distributions.len(),
-            "Number of cable_distributions should match"
-        );
+        let state = new_game(GameMeta::new(10, 3, 2, 2, 1, 3), None, 0);
+        assert_eq!(state.hands.len(), 3, "Number of hands should match the number of players");
         let current_player = 0;
 
-        show_hands(current_player, &initialized_hands, &all_cables);
+        show_hands(&player_view(&state, current_player), true);
+    }
+
+    #[test]
+    fn test_format_range() {
+        assert_eq!(format_range(&[50, 60, 70]), "[5–7]");
+        assert_eq!(format_range(&[25]), "[2.5]");
+        assert_eq!(format_range(&[]), "[?]");
+    }
+
+    #[test]
+    fn test_simulation_setup() {
+        let args = |text: &str| text.split(' ').map(String::from).collect::<Vec<String>>();
+        let (meta, mission, seats, seeds, threads, budget) = simulation_setup(&args("10 --players 4 --blue-max 10 --bot m --seed 5 --threads 2 --mc-iterations 7"))
+            .expect("Expected a valid simulation");
+        assert_eq!((meta.players, meta.blue_max, meta.red_show), (4, 10, 2), "Expected the custom cables on top of the recommended setup");
+        assert!(mission.is_none(), "Expected no mission");
+        assert_eq!(seats, vec![Seat::MonteCarlo; 4], "Expected a Monte Carlo bot on every seat");
+        assert_eq!((seeds, threads), (5..15, 2), "Expected ten seeds from 5 on two threads");
+        assert_eq!((budget.iterations, budget.time), (7, None), "Expected the iterations without a time limit");
+
+        assert!(simulation_setup(&args("10 --player 4")).is_err(), "Expected a misspelled option to be rejected");
+        assert!(simulation_setup(&args("10 --players 4294967299")).is_err(), "Expected a player count beyond u32 to be rejected");
+        assert!(simulation_setup(&args("10 --seed 18446744073709551615")).is_err(), "Expected seeds beyond u64 to be rejected");
+        assert!(simulation_setup(&args("10 --seats bb --bot m")).is_err(), "Expected either seats or a bot");
     }
 }
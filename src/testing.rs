@@ -0,0 +1,76 @@
+//! This module contains helpers shared by the tests of several modules.
+
+use rand::SeedableRng;
+use rand::seq::IteratorRandom;
+use rand_chacha::ChaCha8Rng;
+use std::collections::{HashMap, HashSet};
+
+use crate::character::init_characters;
+use crate::elements::init_death_counter;
+use crate::events::{GameEvent, record_event, state_from_deal};
+use crate::game::{GameMeta, GameState};
+use crate::mission::Mission;
+use crate::player::{Hand, get_cables};
+
+/// Deals a new game and places the info token of every player on a random cable.
+/// The tokens are picked with a copy of the RNG, so that replaying the log restores the same RNG state.
+pub fn new_game(meta: GameMeta, mission: Option<Mission>, seed: u64) -> GameState {
+    let mut state = GameState::deal(meta, mission, seed);
+    let hints = pick_random_hints(&state.hands, &mut state.rng.clone());
+    for player in 0..state.hands.len() {
+        let position = get_cables(&state.hands[player]).iter().position(|id| hints.contains(id)).expect("Every player gets a token");
+        let event = GameEvent::TokenPlaced { player: player as u32, position: position as u32 };
+        record_event(&mut state, event).expect("Initial clue is valid");
+    }
+    state
+}
+
+/// Creates a game with the given cables and hands instead of a random deal. The state is built from a deal event,
+/// so its log replays to the same state.
+pub fn state_with_hands(meta: GameMeta, all_cables: HashMap<u32, u32>, hands: Vec<Vec<u32>>) -> GameState {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let characters = init_characters(meta.players, &mut rng);
+    let death_counter = init_death_counter(meta.players);
+    let deal = GameEvent::Deal { meta, mission: None, seed: 0, rng, all_cables, hands, characters, death_counter };
+    state_from_deal(&deal).expect("Deal event is valid")
+}
+
+/// Picks one random cable per hand to carry an info token at the start of the game. The IDs are returned sorted.
+pub fn pick_random_hints(hands: &[Hand], rng: &mut impl rand::Rng) -> Vec<u32> {
+    let mut cable_ids: HashSet<u32> = HashSet::new();
+    for hand in hands.iter() {
+        let (_, &cable_id) = get_cables(hand).iter().enumerate().choose(rng).unwrap();
+        cable_ids.insert(cable_id);
+    }
+    let mut cable_ids: Vec<u32> = cable_ids.into_iter().collect();
+    cable_ids.sort();
+    cable_ids
+}
+
+mod tests {
+    use super::*;
+    use crate::events::replay;
+    use crate::player::{CableStatus, get_status, init_hands};
+
+    #[test]
+    fn test_pick_random_hints() {
+        let hands = init_hands(vec![
+            vec![5, 2, 3],
+            vec![4, 1],
+        ]);
+        let hints = pick_random_hints(&hands, &mut rand::rng());
+        assert_eq!(hints.len(), 2, "Expected one hint per hand");
+        assert!(hints.is_sorted(), "Expected the hints to be sorted");
+        assert!(get_status(&hands[0]).iter().all(|s| *s == CableStatus::Hidden), "Expected the hands to be unchanged");
+    }
+
+    #[test]
+    fn test_state_with_hands_replays() {
+        let all_cables = HashMap::from([(1, 10), (2, 10), (3, 20), (4, 20)]);
+        let state = state_with_hands(GameMeta::new(2, 0, 0, 0, 0, 2), all_cables, vec![vec![1, 3], vec![2, 4]]);
+        let replayed = replay(&state.log).unwrap();
+        let cables = |state: &GameState| -> Vec<Vec<u32>> { state.hands.iter().map(|hand| get_cables(hand).to_vec()).collect() };
+        assert_eq!(cables(&replayed), cables(&state), "Expected the log to replay to the same hands");
+        assert_eq!(replayed.all_cables, state.all_cables, "Expected the log to replay to the same cables");
+    }
+}
@@ -0,0 +1,149 @@
+//! This module projects the game state onto what a single player is allowed to see.
+//! The own rack is fully visible, of the teammates' racks only revealed cables and info tokens are.
+//! Every renderer and bot works on a `PlayerView`, so hidden information cannot leak by accident.
+
+use serde::{Deserialize, Serialize};
+
+use crate::character::Character;
+use crate::elements::{Equipment, get_value, get_yel_red_info};
+use crate::game::{GameMeta, GameState, available_equipment};
+use crate::player::{CableStatus, Hand, Label, get_announced_cables, get_cables, get_labels, get_status};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SlotView {
+    pub status: CableStatus,
+    /// Value of the cable, `None` if it is hidden from the viewing player.
+    pub value: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RackView {
+    pub slots: Vec<SlotView>,
+    pub labels: Vec<Label>,
+    /// Values the player has publicly claimed to hold and not cut yet, e.g. after a failed dual cut.
+    pub claims: Vec<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlayerView {
+    /// The viewing player.
+    pub player: u32,
+    pub meta: GameMeta,
+    /// The racks of all players, indexed by player.
+    pub racks: Vec<RackView>,
+    /// Values of the red cables shown at the start, `meta.red_keep` of them are in the game.
+    pub red_values: Vec<u32>,
+    /// Values of the yellow cables shown at the start, `meta.yellow_keep` of them are in the game.
+    pub yellow_values: Vec<u32>,
+    pub characters: Vec<Character>,
+    /// Equipment which can still be used.
+    pub equipment: Vec<Equipment>,
+    pub death_counter: u32,
+    pub current_player: u32,
+    pub turn: u32,
+}
+
+/// Computes what `player` can see of the game.
+pub fn player_view(state: &GameState, player: u32) -> PlayerView {
+    let racks = state.hands.iter().enumerate()
+        .map(|(i, hand)| rack_view(hand, i as u32 == player, state))
+        .collect();
+    let (red_values, yellow_values) = get_yel_red_info(&state.all_cables);
+    let mut red_values: Vec<u32> = red_values.into_iter().collect();
+    red_values.sort();
+    let mut yellow_values: Vec<u32> = yellow_values.into_iter().collect();
+    yellow_values.sort();
+    PlayerView {
+        player,
+        meta: state.meta.clone(),
+        racks,
+        red_values,
+        yellow_values,
+        characters: state.characters.clone(),
+        equipment: available_equipment(state),
+        death_counter: state.death_counter,
+        current_player: state.current_player,
+        turn: state.turn,
+    }
+}
+
+/// Computes what everyone at the table can see: cut cables, info tokens, labels and claims, but no rack contents.
+/// Its `player` is the current player, whose rack is hidden like all others.
+pub fn public_view(state: &GameState) -> PlayerView {
+    let mut view = player_view(state, state.current_player);
+    view.racks = state.hands.iter().map(|hand| rack_view(hand, false, state)).collect();
+    view
+}
+
+fn rack_view(hand: &Hand, own: bool, state: &GameState) -> RackView {
+    let cables = get_cables(hand);
+    let status = get_status(hand);
+    let slots = cables.iter().zip(status)
+        .map(|(id, status)| SlotView {
+            status: *status,
+            value: match own || *status != CableStatus::Hidden {
+                true => Some(get_value(*id, &state.all_cables)),
+                false => None,
+            },
+        })
+        .collect();
+    let claims = get_announced_cables(hand).iter()
+        .filter(|id| cables.iter().position(|c| c == *id).is_some_and(|pos| status[pos] != CableStatus::Revealed))
+        .map(|id| get_value(*id, &state.all_cables))
+        .collect();
+    RackView { slots, labels: get_labels(hand).to_vec(), claims }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::new_game;
+    use crate::events::play_dual_cut;
+    use crate::player::change_cable_status;
+
+    #[test]
+    fn test_player_view_hides_teammates_cables() {
+        let mut state = new_game(GameMeta::new(8, 2, 1, 2, 2, 3), None, 9);
+        let revealed_id = get_cables(&state.hands[2])[0];
+        change_cable_status(revealed_id, &mut state.hands, CableStatus::Revealed);
+
+        let view = player_view(&state, 1);
+        assert_eq!(view.racks.len(), 3, "Expected one rack per player");
+        assert!(view.racks[1].slots.iter().all(|slot| slot.value.is_some()), "Expected the own rack to be fully visible");
+        for (i, rack) in view.racks.iter().enumerate() {
+            if i == 1 {
+                continue;
+            }
+            for slot in &rack.slots {
+                assert_eq!(slot.value.is_some(), slot.status != CableStatus::Hidden, "Expected only revealed cables and clues of teammates to be visible");
+            }
+        }
+        assert_eq!(view.racks[2].slots[0].value, Some(state.all_cables[&revealed_id]), "Expected the revealed cable to show its value");
+        assert_eq!((view.red_values.len(), view.yellow_values.len()), (2, 2), "Expected the shown red and yellow values");
+    }
+
+    #[test]
+    fn test_public_view_hides_all_racks() {
+        let state = new_game(GameMeta::new(8, 2, 1, 2, 2, 3), None, 9);
+        let view = public_view(&state);
+        for rack in &view.racks {
+            for slot in &rack.slots {
+                assert_eq!(slot.value.is_some(), slot.status != CableStatus::Hidden, "Expected only revealed cables and clues to be visible to everyone");
+            }
+        }
+    }
+
+    #[test]
+    fn test_player_view_shows_claims() {
+        let mut state = new_game(GameMeta::new(8, 0, 0, 0, 0, 2), None, 4);
+        let own_value = state.all_cables[&get_cables(&state.hands[0])[0]];
+        let position_teammate = get_cables(&state.hands[1]).iter().zip(get_status(&state.hands[1]))
+            .position(|(id, status)| state.all_cables[id] != own_value && *status == CableStatus::Hidden)
+            .unwrap() as u32;
+        play_dual_cut(&mut state, 1, 0, position_teammate).unwrap();
+
+        let view = player_view(&state, 1);
+        assert_eq!(view.racks[0].claims, vec![own_value], "Expected the failed dual cut to claim the player's value");
+        assert_eq!(view.racks[1].slots[position_teammate as usize].status, CableStatus::Clue, "Expected an info token on the targeted cable");
+    }
+}